@@ -0,0 +1 @@
+ALTER TABLE images DROP COLUMN pending_until;
@@ -0,0 +1,3 @@
+-- images uploaded directly to S3 through a presigned URL stay pending until
+-- the upload is confirmed, and get deleted once pending_until has passed
+ALTER TABLE images ADD COLUMN pending_until TIMESTAMP;
@@ -0,0 +1,3 @@
+DROP TABLE suspected_duplicates;
+DROP TABLE listing_pictures;
+ALTER TABLE images DROP COLUMN phash;
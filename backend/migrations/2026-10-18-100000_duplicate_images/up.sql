@@ -0,0 +1,21 @@
+-- perceptual hash (dHash) of the image, to find reused photos
+ALTER TABLE images ADD COLUMN phash BIGINT;
+
+CREATE TABLE listing_pictures (
+    listing_id uuid NOT NULL REFERENCES listings ON DELETE CASCADE,
+    image uuid NOT NULL REFERENCES images,
+    position SMALLINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (listing_id, image)
+);
+
+-- pictures of a listing that look like pictures uploaded by someone else
+CREATE TABLE suspected_duplicates (
+    id uuid PRIMARY KEY NOT NULL DEFAULT gen_random_uuid(),
+    listing_id uuid NOT NULL REFERENCES listings ON DELETE CASCADE,
+    image uuid NOT NULL REFERENCES images ON DELETE CASCADE,
+    matched_image uuid NOT NULL REFERENCES images ON DELETE CASCADE,
+    -- number of differing bits between the two hashes
+    distance SMALLINT NOT NULL,
+    flagged_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (listing_id, image, matched_image)
+);
@@ -0,0 +1,3 @@
+DROP TABLE upload_quotas;
+DROP INDEX images_uploaded_by_user_index;
+ALTER TABLE images DROP COLUMN size_bytes;
@@ -0,0 +1,11 @@
+ALTER TABLE images ADD COLUMN size_bytes BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX images_uploaded_by_user_index ON images (uploaded_by_user, upload_date);
+
+-- per user overrides of the configured upload quota, NULL means the default
+CREATE TABLE upload_quotas (
+    user_id uuid PRIMARY KEY NOT NULL,
+    max_bytes BIGINT,
+    max_images BIGINT,
+    max_per_hour BIGINT
+);
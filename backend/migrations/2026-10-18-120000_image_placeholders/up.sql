@@ -0,0 +1,3 @@
+-- shown while the actual image is loading
+ALTER TABLE images ADD COLUMN blurhash VARCHAR(64);
+ALTER TABLE images ADD COLUMN dominant_color VARCHAR(7);
@@ -0,0 +1,2 @@
+DROP TABLE recognitions;
+ALTER TABLE images DROP COLUMN content_hash;
@@ -0,0 +1,13 @@
+-- sha256 of the uploaded bytes, NULL for images uploaded before this was added
+ALTER TABLE images ADD COLUMN content_hash VARCHAR(64);
+
+-- cached plant recognition results, so we don't ask plantnet twice for the same pictures
+CREATE TABLE recognitions (
+    id uuid PRIMARY KEY NOT NULL DEFAULT gen_random_uuid(),
+    -- sha256 of the sorted image content hashes and the location
+    cache_key VARCHAR(64) UNIQUE NOT NULL,
+    images uuid[] NOT NULL,
+    location geography(POINT,4326),
+    results jsonb NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
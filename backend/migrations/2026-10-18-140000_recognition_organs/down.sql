@@ -0,0 +1,4 @@
+ALTER TABLE recognitions DROP COLUMN region;
+ALTER TABLE recognitions DROP COLUMN language;
+ALTER TABLE recognitions DROP COLUMN organs;
+DROP TYPE plant_organ;
@@ -0,0 +1,7 @@
+CREATE TYPE plant_organ AS ENUM ('auto', 'leaf', 'flower', 'fruit', 'bark');
+
+-- one organ per image, in the same order as images
+ALTER TABLE recognitions ADD COLUMN organs plant_organ[] NOT NULL DEFAULT '{}';
+ALTER TABLE recognitions ADD COLUMN language VARCHAR(8) NOT NULL DEFAULT 'en';
+-- used to pick the flora, might be the user's location if the request had none
+ALTER TABLE recognitions ADD COLUMN region geography(POINT,4326);
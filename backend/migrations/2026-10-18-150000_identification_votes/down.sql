@@ -0,0 +1,4 @@
+DROP INDEX listings_verified_plant_index;
+DROP TABLE identification_votes;
+ALTER TABLE listings DROP COLUMN identification_state;
+DROP TYPE identification_state;
@@ -0,0 +1,18 @@
+CREATE TYPE identification_state AS ENUM ('ai_suggested', 'author_confirmed', 'community_verified', 'disputed');
+
+-- NULL while the listing has no identified plant
+ALTER TABLE listings ADD COLUMN identification_state identification_state;
+
+UPDATE listings SET identification_state = 'ai_suggested' WHERE identified_plant IS NOT NULL;
+
+-- one vote per user and listing, the vote of the author is their confirmation
+CREATE TABLE identification_votes (
+    listing_id uuid NOT NULL REFERENCES listings ON DELETE CASCADE,
+    voter uuid NOT NULL,
+    plant uuid NOT NULL REFERENCES plants,
+    voted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (listing_id, voter)
+);
+
+CREATE INDEX listings_verified_plant_index ON listings (identified_plant)
+    WHERE identification_state = 'community_verified';
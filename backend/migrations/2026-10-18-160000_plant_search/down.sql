@@ -0,0 +1,3 @@
+DROP INDEX plants_species_trgm_index;
+DROP INDEX plants_human_name_trgm_index;
+DROP EXTENSION IF EXISTS pg_trgm;
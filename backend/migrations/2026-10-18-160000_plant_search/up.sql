@@ -0,0 +1,4 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX plants_human_name_trgm_index ON plants USING gin (human_name gin_trgm_ops);
+CREATE INDEX plants_species_trgm_index ON plants USING gin (species gin_trgm_ops);
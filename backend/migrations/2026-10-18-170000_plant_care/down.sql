@@ -0,0 +1,14 @@
+DROP TABLE plant_edits;
+DROP TYPE plant_edit_status;
+
+ALTER TABLE plants DROP COLUMN season;
+ALTER TABLE plants DROP COLUMN propagation_methods;
+ALTER TABLE plants DROP COLUMN toxic_to_pets;
+ALTER TABLE plants DROP COLUMN hardiness_zone;
+ALTER TABLE plants DROP COLUMN light;
+ALTER TABLE plants DROP COLUMN watering;
+
+DROP TYPE plant_season;
+DROP TYPE propagation_method;
+DROP TYPE light_requirement;
+DROP TYPE watering_frequency;
@@ -0,0 +1,29 @@
+CREATE TYPE watering_frequency AS ENUM ('daily', 'twice_weekly', 'weekly', 'biweekly', 'monthly');
+CREATE TYPE light_requirement AS ENUM ('full_sun', 'partial_sun', 'bright_indirect', 'low_light');
+CREATE TYPE propagation_method AS ENUM ('seed', 'cutting', 'division', 'layering', 'offset', 'grafting');
+CREATE TYPE plant_season AS ENUM ('spring', 'summer', 'autumn', 'winter', 'all_year');
+
+ALTER TABLE plants ADD COLUMN watering watering_frequency;
+ALTER TABLE plants ADD COLUMN light light_requirement;
+-- coldest USDA hardiness zone the plant survives outdoors
+ALTER TABLE plants ADD COLUMN hardiness_zone SMALLINT CHECK (hardiness_zone BETWEEN 1 AND 13);
+ALTER TABLE plants ADD COLUMN toxic_to_pets BOOLEAN;
+ALTER TABLE plants ADD COLUMN propagation_methods propagation_method[] NOT NULL DEFAULT '{}';
+-- main growing/flowering season
+ALTER TABLE plants ADD COLUMN season plant_season;
+
+CREATE TYPE plant_edit_status AS ENUM ('pending', 'approved', 'rejected');
+
+-- care information changes, only applied once a moderator approved them
+CREATE TABLE plant_edits (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    plant_id uuid NOT NULL REFERENCES plants ON DELETE CASCADE,
+    author uuid NOT NULL,
+    changes jsonb NOT NULL,
+    status plant_edit_status NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    reviewed_by uuid,
+    reviewed_at TIMESTAMP
+);
+
+CREATE INDEX plant_edits_pending_index ON plant_edits (created_at) WHERE status = 'pending';
@@ -0,0 +1,2 @@
+DROP TABLE plant_similarity;
+DROP TYPE plant_relation;
@@ -0,0 +1,14 @@
+CREATE TYPE plant_relation AS ENUM ('same_genus', 'look_alike', 'companion');
+
+-- undirected edges of the similar plants graph, stored once with plant_a < plant_b
+CREATE TABLE plant_similarity (
+    plant_a uuid NOT NULL REFERENCES plants ON DELETE CASCADE,
+    plant_b uuid NOT NULL REFERENCES plants ON DELETE CASCADE,
+    relation plant_relation NOT NULL,
+    -- how similar the plants are, 1 is the strongest
+    weight REAL NOT NULL DEFAULT 1 CHECK (weight > 0 AND weight <= 1),
+    PRIMARY KEY (plant_a, plant_b, relation),
+    CHECK (plant_a < plant_b)
+);
+
+CREATE INDEX plant_similarity_plant_b_index ON plant_similarity (plant_b);
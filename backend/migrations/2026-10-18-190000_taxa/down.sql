@@ -0,0 +1,3 @@
+ALTER TABLE plants DROP COLUMN genus;
+DROP TABLE taxa;
+DROP TYPE taxon_rank;
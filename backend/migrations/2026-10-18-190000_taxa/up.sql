@@ -0,0 +1,25 @@
+CREATE TYPE taxon_rank AS ENUM ('family', 'genus');
+
+CREATE TABLE taxa (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    rank taxon_rank NOT NULL,
+    name VARCHAR(127) NOT NULL,
+    -- the family of a genus, NULL for families or if we don't know it yet
+    parent uuid REFERENCES taxa,
+    UNIQUE (rank, name)
+);
+
+CREATE INDEX taxa_parent_index ON taxa (parent);
+
+ALTER TABLE plants ADD COLUMN genus uuid REFERENCES taxa;
+CREATE INDEX plants_genus_index ON plants (genus);
+
+-- the genus is the first word of the species, families get filled in
+-- the next time plantnet recognises a plant of the genus
+INSERT INTO taxa (rank, name)
+SELECT DISTINCT 'genus'::taxon_rank, split_part(species, ' ', 1) FROM plants
+WHERE split_part(species, ' ', 1) <> '';
+
+UPDATE plants SET genus = taxa.id
+FROM taxa
+WHERE taxa.rank = 'genus' AND taxa.name = split_part(plants.species, ' ', 1);
@@ -0,0 +1,2 @@
+ALTER TABLE users DROP COLUMN preferred_language;
+DROP TABLE plant_names;
@@ -0,0 +1,21 @@
+-- common names of plants, in every language we know them in
+CREATE TABLE plant_names (
+    plant_id uuid NOT NULL REFERENCES plants ON DELETE CASCADE,
+    -- ISO 639-1 code
+    lang VARCHAR(2) NOT NULL,
+    name VARCHAR(127) NOT NULL,
+    -- the name shown in this language
+    is_primary BOOLEAN NOT NULL DEFAULT false,
+    PRIMARY KEY (plant_id, lang, name)
+);
+
+CREATE UNIQUE INDEX plant_names_primary_index ON plant_names (plant_id, lang) WHERE is_primary;
+CREATE INDEX plant_names_name_trgm_index ON plant_names USING gin (name gin_trgm_ops);
+
+-- recognitions used to ask for English names only
+INSERT INTO plant_names (plant_id, lang, name, is_primary)
+SELECT id, 'en', human_name, true FROM plants
+WHERE human_name <> species;
+
+-- ISO 639-1 code, NULL to use the browser's language
+ALTER TABLE users ADD COLUMN preferred_language VARCHAR(2);
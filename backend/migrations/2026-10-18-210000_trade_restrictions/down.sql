@@ -0,0 +1,2 @@
+DROP TABLE trade_restrictions;
+DROP TYPE restriction_level;
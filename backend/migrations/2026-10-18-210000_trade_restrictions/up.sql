@@ -0,0 +1,22 @@
+CREATE TYPE restriction_level AS ENUM ('warn', 'block');
+
+-- plants that are illegal or harmful to trade, e.g. CITES listed species
+-- or plants that are invasive in a region
+CREATE TABLE trade_restrictions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- either a single species, or all plants of a genus or family
+    powo_id VARCHAR,
+    taxon_id uuid REFERENCES taxa ON DELETE CASCADE,
+    level restriction_level NOT NULL,
+    -- shown to authors of affected listings
+    reason TEXT NOT NULL,
+    -- the rule applies everywhere without a region
+    region_name VARCHAR(127),
+    region geography(Polygon, 4326),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    CHECK ((powo_id IS NULL) <> (taxon_id IS NULL)),
+    CHECK ((region_name IS NULL) = (region IS NULL))
+);
+
+CREATE INDEX trade_restrictions_powo_id_index ON trade_restrictions (powo_id);
+CREATE INDEX trade_restrictions_taxon_id_index ON trade_restrictions (taxon_id);
@@ -0,0 +1,6 @@
+ALTER TABLE listings
+    DROP COLUMN propagation_form,
+    DROP COLUMN quantity,
+    DROP COLUMN pot_size_cm;
+
+DROP TYPE propagation_form;
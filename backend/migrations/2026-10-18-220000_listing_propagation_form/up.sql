@@ -0,0 +1,10 @@
+CREATE TYPE propagation_form AS ENUM ('cutting', 'seedling', 'seeds', 'mature_plant', 'offshoot');
+
+-- listings so far were about whole plants
+ALTER TABLE listings
+    ADD COLUMN propagation_form propagation_form NOT NULL DEFAULT 'mature_plant',
+    ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1 CHECK (quantity > 0),
+    -- diameter of the pot, if the plant comes with one
+    ADD COLUMN pot_size_cm SMALLINT CHECK (pot_size_cm > 0);
+
+CREATE INDEX listings_propagation_form_index ON listings (propagation_form);
@@ -0,0 +1,9 @@
+-- enum values can't be dropped, so the type is recreated without them
+UPDATE listings SET listing_type = 'selling', tradeable = true WHERE listing_type = 'swap';
+UPDATE listings SET listing_type = 'selling' WHERE listing_type = 'giveaway';
+
+ALTER TYPE listing_type RENAME TO listing_type_old;
+CREATE TYPE listing_type AS ENUM ('selling', 'buying');
+ALTER TABLE listings
+    ALTER COLUMN listing_type TYPE listing_type USING listing_type::text::listing_type;
+DROP TYPE listing_type_old;
@@ -0,0 +1,3 @@
+-- giveaways are free, swaps only accept other plants in return
+ALTER TYPE listing_type ADD VALUE 'giveaway';
+ALTER TYPE listing_type ADD VALUE 'swap';
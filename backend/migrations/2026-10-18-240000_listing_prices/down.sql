@@ -0,0 +1,9 @@
+DROP TABLE listing_price_history;
+
+ALTER TABLE listings
+    DROP COLUMN price_mode,
+    DROP COLUMN price_minor,
+    DROP COLUMN currency,
+    DROP COLUMN price_negotiable;
+
+DROP TYPE price_mode;
@@ -0,0 +1,29 @@
+CREATE TYPE price_mode AS ENUM ('fixed', 'free', 'make_an_offer');
+
+-- listings without a price mode don't say anything about the price
+ALTER TABLE listings
+    ADD COLUMN price_mode price_mode,
+    -- in the smallest unit of the currency, e.g. cents
+    ADD COLUMN price_minor BIGINT CHECK (price_minor >= 0),
+    -- ISO 4217 code, e.g. EUR
+    ADD COLUMN currency CHAR(3),
+    ADD COLUMN price_negotiable BOOLEAN NOT NULL DEFAULT false,
+    ADD CONSTRAINT listings_fixed_price_check CHECK (
+        (price_mode IS NOT DISTINCT FROM 'fixed') = (price_minor IS NOT NULL)
+        AND (price_minor IS NULL) = (currency IS NULL)
+    );
+
+CREATE INDEX listings_price_index ON listings (currency, price_minor);
+
+-- every price a listing had, the newest one is the current price
+CREATE TABLE listing_price_history (
+    id uuid PRIMARY KEY NOT NULL DEFAULT gen_random_uuid(),
+    listing_id uuid NOT NULL REFERENCES listings ON DELETE CASCADE,
+    price_mode price_mode,
+    price_minor BIGINT,
+    currency CHAR(3),
+    price_negotiable BOOLEAN NOT NULL,
+    changed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX listing_price_history_listing_index ON listing_price_history (listing_id, changed_at);
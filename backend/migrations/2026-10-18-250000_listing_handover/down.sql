@@ -0,0 +1,7 @@
+ALTER TABLE listings
+    DROP COLUMN local_pickup,
+    DROP COLUMN shipping,
+    DROP COLUMN shipping_cost_minor,
+    DROP COLUMN shipping_currency,
+    DROP COLUMN meet_halfway,
+    DROP COLUMN max_pickup_distance_km;
@@ -0,0 +1,18 @@
+-- listings so far were picked up locally
+ALTER TABLE listings
+    ADD COLUMN local_pickup BOOLEAN NOT NULL DEFAULT true,
+    ADD COLUMN shipping BOOLEAN NOT NULL DEFAULT false,
+    -- in the smallest unit of the currency, unknown if null
+    ADD COLUMN shipping_cost_minor BIGINT CHECK (shipping_cost_minor >= 0),
+    ADD COLUMN shipping_currency CHAR(3),
+    ADD COLUMN meet_halfway BOOLEAN NOT NULL DEFAULT false,
+    -- how far the author lets people come from to pick it up or meet
+    ADD COLUMN max_pickup_distance_km DOUBLE PRECISION CHECK (max_pickup_distance_km > 0),
+    ADD CONSTRAINT listings_handover_check CHECK (
+        (local_pickup OR shipping OR meet_halfway)
+        AND (shipping_cost_minor IS NULL) = (shipping_currency IS NULL)
+        AND (shipping OR shipping_cost_minor IS NULL)
+        AND (local_pickup OR meet_halfway OR max_pickup_distance_km IS NULL)
+    );
+
+CREATE INDEX listings_shipping_index ON listings (shipping) WHERE shipping;
@@ -0,0 +1,2 @@
+DROP TABLE listing_draft_pictures;
+DROP TABLE listing_drafts;
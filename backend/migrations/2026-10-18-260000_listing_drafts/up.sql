@@ -0,0 +1,40 @@
+-- listings that aren't published yet, every field is optional until then.
+-- The fields are checked when the draft gets published as a listing.
+CREATE TABLE listing_drafts (
+    id uuid PRIMARY KEY NOT NULL DEFAULT gen_random_uuid(),
+    author uuid NOT NULL,
+    title VARCHAR(120),
+    description VARCHAR(1023),
+    listing_type listing_type,
+    tradeable BOOLEAN,
+    identified_plant uuid REFERENCES plants ON DELETE SET NULL,
+    propagation_form propagation_form,
+    quantity INTEGER,
+    pot_size_cm SMALLINT,
+    price_mode price_mode,
+    price_minor BIGINT,
+    currency CHAR(3),
+    price_negotiable BOOLEAN,
+    local_pickup BOOLEAN,
+    shipping BOOLEAN,
+    shipping_cost_minor BIGINT,
+    shipping_currency CHAR(3),
+    meet_halfway BOOLEAN,
+    max_pickup_distance_km DOUBLE PRECISION,
+    -- published as soon as this has passed
+    publish_at TIMESTAMP,
+    -- why publishing it at publish_at didn't work
+    publish_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX listing_drafts_author_index ON listing_drafts (author);
+CREATE INDEX listing_drafts_publish_at_index ON listing_drafts (publish_at) WHERE publish_at IS NOT NULL;
+
+CREATE TABLE listing_draft_pictures (
+    draft_id uuid NOT NULL REFERENCES listing_drafts ON DELETE CASCADE,
+    image uuid NOT NULL REFERENCES images,
+    position SMALLINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (draft_id, image)
+);
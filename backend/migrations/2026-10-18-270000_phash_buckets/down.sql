@@ -0,0 +1,7 @@
+DROP INDEX images_phash_band_0;
+DROP INDEX images_phash_band_1;
+DROP INDEX images_phash_band_2;
+DROP INDEX images_phash_band_3;
+DROP INDEX images_phash_band_4;
+DROP INDEX images_phash_band_5;
+DROP INDEX images_phash_band_6;
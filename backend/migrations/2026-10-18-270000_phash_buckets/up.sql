@@ -0,0 +1,10 @@
+-- Two hashes at most 6 bits apart have at least one of these 7 bands in
+-- common, so duplicate candidates can be looked up by band instead of
+-- comparing against every image. Keep in sync with DUPLICATE_MAX_DISTANCE.
+CREATE INDEX images_phash_band_0 ON images (((phash >> 0) & 511)) WHERE phash IS NOT NULL;
+CREATE INDEX images_phash_band_1 ON images (((phash >> 9) & 511)) WHERE phash IS NOT NULL;
+CREATE INDEX images_phash_band_2 ON images (((phash >> 18) & 511)) WHERE phash IS NOT NULL;
+CREATE INDEX images_phash_band_3 ON images (((phash >> 27) & 511)) WHERE phash IS NOT NULL;
+CREATE INDEX images_phash_band_4 ON images (((phash >> 36) & 511)) WHERE phash IS NOT NULL;
+CREATE INDEX images_phash_band_5 ON images (((phash >> 45) & 511)) WHERE phash IS NOT NULL;
+CREATE INDEX images_phash_band_6 ON images (((phash >> 54) & 1023)) WHERE phash IS NOT NULL;
@@ -0,0 +1,9 @@
+-- the plant picked by the author or the recognition, identified_plant falls
+-- back to it when the community no longer agrees on another one
+ALTER TABLE listings ADD COLUMN suggested_plant uuid REFERENCES plants ON DELETE SET NULL;
+
+-- verified listings lost their pick, the author's vote is the best guess
+UPDATE listings SET suggested_plant = COALESCE(
+    (SELECT plant FROM identification_votes WHERE listing_id = listings.id AND voter = listings.author),
+    identified_plant
+);
@@ -0,0 +1,6 @@
+-- direct uploads go to this key and are copied to file_key when confirmed,
+-- so the signed URL can't replace the served image. Kept until the URL
+-- expired, anything uploaded to it late gets deleted then.
+ALTER TABLE images ADD COLUMN staging_key uuid;
+
+CREATE INDEX images_staging_key_index ON images (upload_date) WHERE staging_key IS NOT NULL;
@@ -269,6 +269,18 @@ impl AuthnBackend for AuthState {
 
 pub type AuthSession = axum_login::AuthSession<AuthState>;
 
+/// Whether the logged in user (if any) has the configured admin role.
+pub fn is_admin(auth_session: &AuthSession, config: &AppConfig) -> bool {
+    auth_session.user.as_ref()
+        .is_some_and(|user| user.has_role(config.auth_admin_role()))
+}
+
+/// Whether the logged in user (if any) is an admin or has the configured trusted role.
+pub fn is_trusted(auth_session: &AuthSession, config: &AppConfig) -> bool {
+    is_admin(auth_session, config) || auth_session.user.as_ref()
+        .is_some_and(|user| user.has_role(config.auth_trusted_role()))
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserClaims {
     #[serde(rename = "sub")]
@@ -286,6 +298,12 @@ pub struct User {
     pub access_token: String,
 }
 
+impl User {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.claims.realm_roles.iter().any(|realm_role| realm_role == role)
+    }
+}
+
 // Here we've implemented `Debug` manually to avoid accidentally logging the
 // access token.
 impl std::fmt::Debug for User {
@@ -0,0 +1,441 @@
+use std::{collections::{HashMap, HashSet}, fs::File, io::{BufReader, Read}, path::Path};
+
+use diesel::{prelude::*, upsert::excluded};
+use serde::Deserialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{models::InsertPlant, schema::plants};
+
+use super::{recognition::{insert_or_load_genus, insert_plant_names}, Backend};
+
+/// Plants are written in batches of this size, one transaction each.
+const BATCH_SIZE: usize = 1000;
+
+/// Progress is logged every this many rows.
+const PROGRESS_INTERVAL: usize = 100_000;
+
+/// Downloaded reference data that can be imported into the plant catalogue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CatalogueFormat {
+    /// World Checklist of Vascular Plants, the wcvp.zip download of POWO or
+    /// its extracted wcvp_names.csv. Adds and updates accepted species.
+    Wcvp,
+    /// GBIF backbone taxonomy, backbone.zip or the directory it was extracted
+    /// to. GBIF has no POWO ids, so this only adds GBIF ids, common names (in
+    /// all languages) and taxonomy to plants that are already in the catalogue.
+    GbifBackbone,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportStats {
+    /// Plants that were inserted or updated.
+    pub imported: usize,
+    /// Rows that aren't accepted species, or plants we don't know.
+    pub skipped: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CatalogueImportError {
+    #[error("Couldn't read the file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Couldn't read the archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Invalid row: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("{0} is missing in the archive")]
+    MissingFile(&'static str),
+
+    #[error("DB error: {0}")]
+    Db(#[from] diesel::result::Error),
+}
+
+impl Backend {
+    /// Imports the reference data at `path`. Importing the same data again
+    /// doesn't change anything, plants are matched by their powo id.
+    /// This blocks the runtime thread for a long time, it's meant for the cli.
+    pub async fn import_catalogue(&self, format: CatalogueFormat, path: &Path) -> Result<ImportStats, CatalogueImportError> {
+        let mut con = self.db.lock().await;
+
+        tokio::task::block_in_place(|| match format {
+            CatalogueFormat::Wcvp => read_entry(path, "wcvp_names.csv", |names| import_wcvp(&mut con, names)),
+            CatalogueFormat::GbifBackbone => import_gbif_backbone(&mut con, path),
+        })
+    }
+}
+
+/// Calls `read` with the file `name` in the archive at `path`. `path` can
+/// also be the directory the archive was extracted to, or the file itself.
+fn read_entry<T>(
+    path: &Path,
+    name: &'static str,
+    read: impl FnOnce(&mut dyn Read) -> Result<T, CatalogueImportError>,
+) -> Result<T, CatalogueImportError> {
+    if path.is_dir() {
+        return read(&mut BufReader::new(File::open(path.join(name))?));
+    }
+
+    if !path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("zip")) {
+        return read(&mut BufReader::new(File::open(path)?));
+    }
+
+    let mut archive = zip::ZipArchive::new(BufReader::new(File::open(path)?))?;
+
+    // some archives have everything in a subdirectory
+    let entry_name = archive.file_names()
+        .find(|entry| entry.rsplit('/').next() == Some(name))
+        .map(str::to_string)
+        .ok_or(CatalogueImportError::MissingFile(name))?;
+
+    let mut entry = archive.by_name(&entry_name)?;
+    read(&mut entry)
+}
+
+/// An accepted species of the reference data.
+#[derive(Debug, Clone, PartialEq)]
+struct CatalogueSpecies {
+    powo_id: String,
+    species: String,
+    genus: String,
+    family: String,
+}
+
+/// Row of wcvp_names.csv, only the columns we need.
+#[derive(Deserialize, Debug)]
+struct WcvpName {
+    taxon_rank: String,
+    taxon_status: String,
+    family: String,
+    genus: String,
+    taxon_name: String,
+    powo_id: String,
+}
+
+impl WcvpName {
+    /// None for synonyms, other ranks and names without powo id.
+    fn into_species(self) -> Option<CatalogueSpecies> {
+        let is_species = self.taxon_rank == "Species" && self.taxon_status == "Accepted";
+
+        (is_species && !self.powo_id.is_empty() && !self.genus.is_empty()).then(|| CatalogueSpecies {
+            powo_id: self.powo_id,
+            species: self.taxon_name.chars().take(127).collect(), // plants.species is a VARCHAR(127)
+            genus: self.genus,
+            family: self.family,
+        })
+    }
+}
+
+/// WCVP files are separated with pipes, and don't quote anything.
+fn wcvp_species(names: &mut dyn Read) -> impl Iterator<Item = Result<Option<CatalogueSpecies>, csv::Error>> + '_ {
+    csv::ReaderBuilder::new()
+        .delimiter(b'|')
+        .quoting(false)
+        .flexible(true)
+        .from_reader(names)
+        .into_deserialize()
+        .map(|name| name.map(WcvpName::into_species))
+}
+
+fn import_wcvp(con: &mut PgConnection, names: &mut dyn Read) -> Result<ImportStats, CatalogueImportError> {
+    let mut stats = ImportStats::default();
+    let mut genera = HashMap::new();
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    for (row, species) in wcvp_species(names).enumerate() {
+        match species? {
+            Some(species) => batch.push(species),
+            None => stats.skipped += 1,
+        }
+
+        if batch.len() >= BATCH_SIZE {
+            stats.imported += upsert_species(con, &mut genera, &batch)?;
+            batch.clear();
+        }
+
+        if (row + 1) % PROGRESS_INTERVAL == 0 {
+            info!(rows = row + 1, stats.imported, stats.skipped, "Importing WCVP names");
+        }
+    }
+
+    stats.imported += upsert_species(con, &mut genera, &batch)?;
+
+    info!(stats.imported, stats.skipped, "Imported WCVP names");
+
+    Ok(stats)
+}
+
+/// Inserts the species, or updates their name and genus. The display names
+/// of known plants aren't touched. `genera` caches genus ids by name.
+fn upsert_species(
+    con: &mut PgConnection,
+    genera: &mut HashMap<(String, String), Uuid>,
+    batch: &[CatalogueSpecies],
+) -> Result<usize, CatalogueImportError> {
+    if batch.is_empty() {
+        return Ok(0);
+    }
+
+    con.transaction(|con| {
+        let mut seen = HashSet::new();
+        let mut insert_plants = Vec::with_capacity(batch.len());
+
+        // postgres can't update the same row twice in one statement
+        for species in batch.iter().filter(|species| seen.insert(&species.powo_id)) {
+            let key = (species.genus.clone(), species.family.clone());
+
+            let genus = match genera.get(&key) {
+                Some(genus) => *genus,
+                None => {
+                    let family = Some(species.family.as_str()).filter(|family| !family.is_empty());
+                    let genus = insert_or_load_genus(con, &species.genus, family)?;
+                    genera.insert(key, genus);
+                    genus
+                }
+            };
+
+            insert_plants.push(InsertPlant {
+                powo_id: species.powo_id.clone(),
+                gbif_id: None,
+                human_name: species.species.chars().take(63).collect(), // plants.human_name is a VARCHAR(63)
+                species: species.species.clone(),
+                location: None,
+                produces_fruit: None,
+                description: "".to_string(),
+                genus: Some(genus),
+            });
+        }
+
+        let imported = diesel::insert_into(plants::table)
+            .values(&insert_plants)
+            .on_conflict(plants::powo_id)
+            .do_update()
+            .set((
+                plants::species.eq(excluded(plants::species)),
+                plants::genus.eq(excluded(plants::genus)),
+            ))
+            .execute(con)?;
+
+        Ok(imported)
+    })
+}
+
+/// Row of the backbone's Taxon.tsv, only the columns we need.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GbifTaxon {
+    #[serde(rename = "taxonID")]
+    taxon_id: i32,
+    canonical_name: String,
+    taxon_rank: String,
+    taxonomic_status: String,
+    kingdom: String,
+    family: String,
+    genus: String,
+}
+
+/// Row of the backbone's VernacularName.tsv.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GbifVernacularName {
+    #[serde(rename = "taxonID")]
+    taxon_id: i32,
+    vernacular_name: String,
+    language: String,
+}
+
+/// The backbone files are tab separated, and don't quote anything.
+fn gbif_rows<T: serde::de::DeserializeOwned + 'static>(file: &mut dyn Read) -> impl Iterator<Item = Result<T, csv::Error>> + '_ {
+    csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .quoting(false)
+        .flexible(true)
+        .from_reader(file)
+        .into_deserialize()
+}
+
+/// A catalogue plant found in the backbone.
+#[derive(Debug)]
+struct GbifMatch {
+    plant: Uuid,
+    genus: Option<(String, String)>,
+    /// By ISO 639-1 language code, in the order of the backbone.
+    common_names: HashMap<String, Vec<String>>,
+}
+
+/// A plant of the catalogue, as far as the GBIF import cares.
+#[derive(Queryable, Debug)]
+struct KnownPlant {
+    id: Uuid,
+    species: String,
+    gbif_id: Option<i32>,
+    human_name: String,
+    genus: Option<Uuid>,
+}
+
+fn import_gbif_backbone(con: &mut PgConnection, path: &Path) -> Result<ImportStats, CatalogueImportError> {
+    let known: Vec<KnownPlant> = plants::table
+        .select((plants::id, plants::species, plants::gbif_id, plants::human_name, plants::genus))
+        .load(con)?;
+
+    let by_id: HashMap<Uuid, &KnownPlant> = known.iter()
+        .map(|plant| (plant.id, plant))
+        .collect();
+    let by_gbif_id: HashMap<i32, &KnownPlant> = known.iter()
+        .filter_map(|plant| plant.gbif_id.map(|gbif_id| (gbif_id, plant)))
+        .collect();
+    let by_species: HashMap<&str, &KnownPlant> = known.iter()
+        .map(|plant| (plant.species.as_str(), plant))
+        .collect();
+
+    let mut stats = ImportStats::default();
+    let mut matches: HashMap<i32, GbifMatch> = HashMap::new();
+
+    read_entry(path, "Taxon.tsv", |taxa| {
+        for (row, taxon) in gbif_rows::<GbifTaxon>(taxa).enumerate() {
+            let taxon = taxon?;
+
+            let is_species = taxon.kingdom == "Plantae" && taxon.taxon_rank == "species" && taxon.taxonomic_status == "accepted";
+            let known_plant = by_gbif_id.get(&taxon.taxon_id)
+                .or_else(|| by_species.get(taxon.canonical_name.as_str()));
+
+            match known_plant {
+                Some(plant) if is_species => {
+                    let genus = (!taxon.genus.is_empty()).then_some((taxon.genus, taxon.family));
+                    matches.insert(taxon.taxon_id, GbifMatch { plant: plant.id, genus, common_names: HashMap::new() });
+                }
+                _ => stats.skipped += 1,
+            }
+
+            if (row + 1) % PROGRESS_INTERVAL == 0 {
+                info!(rows = row + 1, matched = matches.len(), "Reading GBIF taxa");
+            }
+        }
+
+        Ok(())
+    })?;
+
+    read_entry(path, "VernacularName.tsv", |names| {
+        for name in gbif_rows::<GbifVernacularName>(names) {
+            let name = name?;
+
+            // plant_names.lang only fits ISO 639-1 codes
+            let is_iso_639_1 = name.language.len() == 2 && name.language.chars().all(|c| c.is_ascii_lowercase());
+            if !is_iso_639_1 || name.vernacular_name.is_empty() {
+                continue;
+            }
+
+            if let Some(gbif_match) = matches.get_mut(&name.taxon_id) {
+                gbif_match.common_names.entry(name.language).or_default().push(name.vernacular_name);
+            }
+        }
+
+        Ok(())
+    })?;
+
+    let mut genera = HashMap::new();
+
+    for batch in matches.into_iter().collect::<Vec<_>>().chunks(BATCH_SIZE) {
+        con.transaction(|con| {
+            for (gbif_id, gbif_match) in batch {
+                let plant = by_id[&gbif_match.plant];
+
+                // only plants that are still named after their species get a common name
+                let human_name = gbif_match.common_names.get("en")
+                    .and_then(|names| names.first())
+                    .filter(|_| plant.human_name == plant.species)
+                    .map(|name| name.chars().take(63).collect::<String>())
+                    .unwrap_or_else(|| plant.human_name.clone());
+
+                let genus = match (plant.genus, &gbif_match.genus) {
+                    (Some(genus), _) => Some(genus),
+                    (None, Some(key)) => {
+                        let genus = match genera.get(key) {
+                            Some(genus) => *genus,
+                            None => {
+                                let family = Some(key.1.as_str()).filter(|family| !family.is_empty());
+                                let genus = insert_or_load_genus(con, &key.0, family)?;
+                                genera.insert(key.clone(), genus);
+                                genus
+                            }
+                        };
+                        Some(genus)
+                    }
+                    (None, None) => None,
+                };
+
+                diesel::update(plants::table.find(plant.id))
+                    .set((
+                        plants::gbif_id.eq(gbif_id),
+                        plants::human_name.eq(human_name),
+                        plants::genus.eq(genus),
+                    ))
+                    .execute(con)?;
+
+                for (language, names) in &gbif_match.common_names {
+                    insert_plant_names(con, plant.id, language, names)?;
+                }
+            }
+
+            Ok::<_, CatalogueImportError>(())
+        })?;
+
+        stats.imported += batch.len();
+        info!(stats.imported, "Updating plants from the GBIF backbone");
+    }
+
+    info!(stats.imported, stats.skipped, "Imported GBIF backbone");
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wcvp_accepted_species_are_read() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/catalogue/wcvp_names.csv");
+
+        let species = read_entry(&path, "wcvp_names.csv", |names| {
+            Ok(wcvp_species(names).collect::<Result<Vec<_>, _>>()?)
+        }).unwrap();
+
+        let accepted: Vec<_> = species.into_iter().flatten().collect();
+
+        assert_eq!(accepted, vec![
+            CatalogueSpecies {
+                powo_id: "87301-1".to_string(),
+                species: "Monstera deliciosa".to_string(),
+                genus: "Monstera".to_string(),
+                family: "Araceae".to_string(),
+            },
+            CatalogueSpecies {
+                powo_id: "88120-1".to_string(),
+                species: "Monstera adansonii".to_string(),
+                genus: "Monstera".to_string(),
+                family: "Araceae".to_string(),
+            },
+        ]);
+    }
+
+    #[test]
+    fn gbif_files_are_read_from_directory() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/catalogue/gbif");
+
+        let taxa: Vec<GbifTaxon> = read_entry(&path, "Taxon.tsv", |taxa| {
+            Ok(gbif_rows(taxa).collect::<Result<Vec<_>, _>>()?)
+        }).unwrap();
+        assert_eq!(taxa.len(), 3);
+        assert_eq!(taxa[0].canonical_name, "Monstera deliciosa");
+
+        let names: Vec<GbifVernacularName> = read_entry(&path, "VernacularName.tsv", |names| {
+            Ok(gbif_rows(names).collect::<Result<Vec<_>, _>>()?)
+        }).unwrap();
+        assert_eq!(names[0].vernacular_name, "Fensterblatt");
+
+        assert!(matches!(read_entry(&path, "Missing.tsv", |_| Ok(())), Err(CatalogueImportError::Io(_))));
+    }
+}
@@ -0,0 +1,164 @@
+use std::{collections::HashMap, io::Cursor};
+
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use bytes::Bytes;
+use image::{imageops::FilterType, DynamicImage, ImageFormat, ImageResult, RgbaImage};
+use sha2::{Digest, Sha256};
+
+/// Everything we compute from an image's pixels when it gets uploaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageAnalysis {
+    pub phash: i64,
+    pub blurhash: String,
+    /// As a css hex color, e.g. #4a7b2f
+    pub dominant_color: String,
+}
+
+impl ImageAnalysis {
+    /// Decodes the image and analyzes it. This is CPU heavy,
+    /// so call it from a blocking task.
+    pub fn from_bytes(data: &Bytes) -> ImageResult<Self> {
+        let image = image::load_from_memory(data)?;
+
+        // blurhash and color don't need more detail than this
+        let thumbnail = image.thumbnail(64, 64).to_rgba8();
+
+        Ok(Self {
+            phash: difference_hash(&image),
+            blurhash: blurhash(&thumbnail),
+            dominant_color: dominant_color(&thumbnail),
+        })
+    }
+}
+
+/// MIME type of the image data, judged by its magic bytes.
+pub fn content_type(data: &[u8]) -> &'static str {
+    image::guess_format(data).map_or("application/octet-stream", |format| format.to_mime_type())
+}
+
+/// sha256 of the raw image data as hex. Unlike the phash this only matches
+/// byte for byte identical uploads.
+pub fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Components used for the blurhash, 4x3 is what the reference
+/// implementation recommends for most images.
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+fn blurhash(thumbnail: &RgbaImage) -> String {
+    let (components_x, components_y) = BLURHASH_COMPONENTS;
+    blurhash::encode(components_x, components_y, thumbnail.width(), thumbnail.height(), thumbnail.as_raw())
+        .expect("blurhash component counts are valid")
+}
+
+/// Sorts all pixels into coarse color buckets, and returns the average
+/// color of the fullest bucket. Transparent pixels are ignored.
+fn dominant_color(thumbnail: &RgbaImage) -> String {
+    let mut buckets: HashMap<[u8; 3], (u32, [u32; 3])> = HashMap::new();
+
+    for pixel in thumbnail.pixels().filter(|pixel| pixel[3] >= 128) {
+        let [r, g, b, _] = pixel.0;
+        let (count, sum) = buckets.entry([r >> 4, g >> 4, b >> 4]).or_default();
+        *count += 1;
+        sum[0] += u32::from(r);
+        sum[1] += u32::from(g);
+        sum[2] += u32::from(b);
+    }
+
+    let Some((count, [r, g, b])) = buckets.into_values().max_by_key(|(count, _)| *count) else {
+        return "#808080".to_string();
+    };
+
+    format!("#{:02x}{:02x}{:02x}", r / count, g / count, b / count)
+}
+
+/// Decodes a blurhash into a tiny PNG as a data URI, to be shown (scaled up)
+/// while the real image loads. Returns None for invalid blurhashes.
+pub fn blurhash_data_uri(blurhash: &str) -> Option<String> {
+    const SIZE: u32 = 32;
+
+    let pixels = blurhash::decode(blurhash, SIZE, SIZE, 1.0).ok()?;
+    let image = RgbaImage::from_raw(SIZE, SIZE, pixels)?;
+
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, ImageFormat::Png).ok()?;
+
+    Some(format!("data:image/png;base64,{}", BASE64_STANDARD.encode(png.into_inner())))
+}
+
+/// Perceptual difference hash (dHash): scales the image down to 9x8 grayscale
+/// pixels and records for every pixel whether it's brighter than its right
+/// neighbour. Similar looking images (resized, recompressed, slightly edited)
+/// end up with hashes that only differ in a few bits.
+pub fn difference_hash(image: &DynamicImage) -> i64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+
+    // stored as BIGINT in postgres, so we only care about the bits
+    hash as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, Rgb, RgbImage};
+
+    use super::*;
+
+    fn hamming_distance(a: i64, b: i64) -> u32 {
+        (a ^ b).count_ones()
+    }
+
+    fn gradient(width: u32, height: u32, invert: bool) -> DynamicImage {
+        let image = RgbImage::from_fn(width, height, |x, y| {
+            let value = ((x * 255) / width) as u8 ^ ((y * 64 / height) as u8);
+            let value = if invert { 255 - value } else { value };
+            Rgb([value, value / 2, 255 - value])
+        });
+        DynamicImage::ImageRgb8(image)
+    }
+
+    #[test]
+    fn placeholder_of_single_color_image() {
+        let image = RgbImage::from_pixel(40, 30, Rgb([0x4a, 0x7b, 0x2f]));
+        let thumbnail = DynamicImage::ImageRgb8(image).to_rgba8();
+
+        assert_eq!(dominant_color(&thumbnail), "#4a7b2f");
+
+        let hash = blurhash(&thumbnail);
+        let data_uri = blurhash_data_uri(&hash).unwrap();
+        assert!(data_uri.starts_with("data:image/png;base64,"));
+
+        assert_eq!(blurhash_data_uri("not a blurhash"), None);
+    }
+
+    #[test]
+    fn resized_images_have_similar_hashes() {
+        let original = difference_hash(&gradient(640, 480, false));
+        let resized = difference_hash(&gradient(320, 240, false));
+        let different = difference_hash(&gradient(640, 480, true));
+
+        assert!(hamming_distance(original, resized) <= 4);
+        assert!(hamming_distance(original, different) > 20);
+    }
+
+    #[test]
+    fn content_type_of_uploads() {
+        let mut png = Cursor::new(Vec::new());
+        gradient(16, 16, false).write_to(&mut png, ImageFormat::Png).unwrap();
+        let mut jpeg = Cursor::new(Vec::new());
+        gradient(16, 16, false).write_to(&mut jpeg, ImageFormat::Jpeg).unwrap();
+
+        assert_eq!(content_type(png.get_ref()), "image/png");
+        assert_eq!(content_type(jpeg.get_ref()), "image/jpeg");
+        assert_eq!(content_type(b"not really a jpeg"), "application/octet-stream");
+    }
+}
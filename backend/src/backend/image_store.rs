@@ -0,0 +1,567 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{stream::BoxStream, StreamExt};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+
+/// Storage for the raw image files, keyed by the `file_key` of their
+/// row in the `images` table.
+#[async_trait]
+pub trait ImageStore: Send + Sync {
+    async fn put(&self, key: Uuid, data: Bytes, content_type: &str) -> ImageStoreResult<()>;
+
+    /// Returns None if there is no image with this key.
+    async fn get(&self, key: Uuid) -> ImageStoreResult<Option<StoredImage>>;
+
+    /// Deleting a key that doesn't exist is not an error.
+    async fn delete(&self, key: Uuid) -> ImageStoreResult<()>;
+
+    /// Like `get`, but without fetching the image data.
+    async fn head(&self, key: Uuid) -> ImageStoreResult<Option<ImageMetadata>>;
+
+    /// Like `get`, but streams the image (or only the given range of it)
+    /// instead of loading it into memory.
+    /// The range should be inside the image, see `ByteRange::resolve`.
+    /// If the image got shorter since, the range is clamped to it.
+    async fn get_stream(&self, key: Uuid, range: Option<ByteRange>) -> ImageStoreResult<Option<ImageStream>> {
+        let Some(metadata) = self.head(key).await? else {
+            return Ok(None);
+        };
+        let Some(image) = self.get(key).await? else {
+            return Ok(None);
+        };
+
+        // describe what we actually got, the image could have been replaced after the head
+        let metadata = ImageMetadata { size: image.data.len() as u64, ..metadata };
+        let range = range.and_then(|range| range.clamp(metadata.size));
+
+        let data = match range {
+            Some(range) => image.data.slice(range.start as usize..=range.end as usize),
+            None => image.data,
+        };
+
+        Ok(Some(ImageStream {
+            metadata,
+            range,
+            body: futures::stream::once(async { Ok(data) }).boxed(),
+        }))
+    }
+
+    async fn list(&self) -> ImageStoreResult<Vec<Uuid>>;
+
+    /// Returns an URL the client can PUT the image to directly, without
+    /// going through our server. Fails with `ImageStoreError::Unsupported`
+    /// if the store can't be reached by clients.
+    async fn presign_put(&self, _key: Uuid, _content_type: &str, _expires_in: Duration)
+        -> ImageStoreResult<String>
+    {
+        Err(ImageStoreError::Unsupported)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageStoreKind {
+    #[default]
+    S3,
+    Filesystem,
+    Memory,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredImage {
+    pub content_type: String,
+    pub data: Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageMetadata {
+    pub content_type: String,
+    pub size: u64,
+    /// Already quoted, so it can be used as the ETag header as is.
+    pub etag: Option<String>,
+}
+
+pub struct ImageStream {
+    /// Metadata of the whole image, not just the range.
+    pub metadata: ImageMetadata,
+    pub range: Option<ByteRange>,
+    pub body: BoxStream<'static, ImageStoreResult<Bytes>>,
+}
+
+impl ImageStream {
+    pub fn content_length(&self) -> u64 {
+        self.range.map_or(self.metadata.size, |range| range.len())
+    }
+}
+
+/// Inclusive range of bytes, like in the HTTP Range header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Resolves a range spec from the Range header (the part after "bytes=")
+    /// against the size of the image. Only single ranges are supported.
+    /// Returns None if the range is invalid or can't be satisfied.
+    pub fn resolve(spec: &str, size: u64) -> Option<ByteRange> {
+        let (start, end) = spec.trim().split_once('-')?;
+        let (start, end) = (start.trim(), end.trim());
+
+        let (start, end) = if start.is_empty() {
+            // suffix range, the last n bytes
+            let suffix: u64 = end.parse().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (size.saturating_sub(suffix), size.checked_sub(1)?)
+        } else {
+            let start: u64 = start.parse().ok()?;
+            let end = if end.is_empty() {
+                size.checked_sub(1)?
+            } else {
+                end.parse::<u64>().ok()?.min(size.checked_sub(1)?)
+            };
+            (start, end)
+        };
+
+        (start <= end && end < size).then_some(ByteRange { start, end })
+    }
+
+    /// Cuts the range off at the end of an image with this size.
+    /// Returns None if nothing of it is left.
+    pub fn clamp(self, size: u64) -> Option<ByteRange> {
+        let end = self.end.min(size.checked_sub(1)?);
+        (self.start <= end).then_some(ByteRange { start: self.start, end })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImageStoreError {
+    #[error("S3 error: {0}")]
+    S3(Box<aws_sdk_s3::Error>),
+
+    #[error("S3 bytestream error: {0}")]
+    S3Bytestream(#[from] aws_sdk_s3::primitives::ByteStreamError),
+
+    #[error("S3 presigning config error: {0}")]
+    S3Presigning(#[from] aws_sdk_s3::presigning::PresigningConfigError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Operation not supported by this image store")]
+    Unsupported,
+}
+
+pub type ImageStoreResult<T> = Result<T, ImageStoreError>;
+
+// boxed, because the S3 error is huge and makes every result that contains it huge
+impl From<aws_sdk_s3::Error> for ImageStoreError {
+    fn from(err: aws_sdk_s3::Error) -> Self {
+        ImageStoreError::S3(Box::new(err))
+    }
+}
+
+const DEFAULT_CONTENT_TYPE: &str = "image/jpeg";
+
+/// Creates the image store selected by `image_store` in the config.
+pub async fn from_config(config: &AppConfig) -> Arc<dyn ImageStore> {
+    match config.image_store() {
+        ImageStoreKind::S3 => {
+            let store = s3::S3ImageStore::new(
+                config.s3_access_key().expect("s3_access_key is required for the s3 image store"),
+                config.s3_secret_key().expect("s3_secret_key is required for the s3 image store"),
+                config.s3_endpoint().expect("s3_endpoint is required for the s3 image store"),
+                config.s3_images_bucket().expect("s3_images_bucket is required for the s3 image store"),
+            ).await;
+            Arc::new(store)
+        }
+        ImageStoreKind::Filesystem => {
+            let path = config.image_store_path()
+                .expect("image_store_path is required for the filesystem image store");
+            let store = filesystem::FilesystemImageStore::new(path).await
+                .unwrap_or_else(|err| panic!("Couldn't create image directory {path}, error: {err}"));
+            Arc::new(store)
+        }
+        ImageStoreKind::Memory => Arc::new(memory::MemoryImageStore::default()),
+    }
+}
+
+pub mod s3 {
+    use aws_config::{meta::region::RegionProviderChain, BehaviorVersion};
+    use aws_sdk_s3::{config::Credentials, presigning::PresigningConfig};
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    pub struct S3ImageStore {
+        client: aws_sdk_s3::Client,
+        bucket: String,
+    }
+
+    impl S3ImageStore {
+        /// Connects to S3 and creates the bucket if it doesn't exist yet.
+        pub async fn new(access_key: &str, secret_key: &str, endpoint: &str, bucket: &str) -> Self {
+            let region_provider = RegionProviderChain::default_provider().or_else("us-east-1");
+            let credentials_provider = Credentials::new(access_key, secret_key, None, None, "Environment");
+            let s3_config = aws_config::defaults(BehaviorVersion::latest())
+                .region(region_provider)
+                .endpoint_url(endpoint)
+                .credentials_provider(credentials_provider)
+                .load().await;
+
+            let s3_config: aws_sdk_s3::Config = (&s3_config).into();
+            let s3_config = s3_config.to_builder()
+                .force_path_style(true)
+                .build();
+
+            let client = aws_sdk_s3::Client::from_conf(s3_config);
+
+            let head_bucket = client.head_bucket()
+                .bucket(bucket)
+                .send().await;
+
+            if head_bucket.is_err_and(
+                |err| err.into_service_error().is_not_found()
+            ) {
+                client.create_bucket()
+                    .bucket(bucket)
+                    .send().await.unwrap();
+            }
+
+            Self { client, bucket: bucket.to_owned() }
+        }
+    }
+
+    #[async_trait]
+    impl ImageStore for S3ImageStore {
+        async fn put(&self, key: Uuid, data: Bytes, content_type: &str) -> ImageStoreResult<()> {
+            self.client.put_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .body(data.into())
+                .content_type(content_type)
+                .send()
+                .await.map_err(aws_sdk_s3::Error::from)?;
+
+            Ok(())
+        }
+
+        async fn get(&self, key: Uuid) -> ImageStoreResult<Option<StoredImage>> {
+            let result = self.client.get_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .send()
+                .await;
+
+            match result {
+                Err(err) if err.as_service_error().is_some_and(|e| e.is_no_such_key()) => {
+                    Ok(None)
+                }
+                Err(err) => Err(aws_sdk_s3::Error::from(err).into()),
+                Ok(result) => {
+                    let data = result.body.collect().await?.into_bytes();
+                    let content_type = result.content_type
+                        .unwrap_or(DEFAULT_CONTENT_TYPE.to_string());
+                    Ok(Some(StoredImage { content_type, data }))
+                }
+            }
+        }
+
+        async fn delete(&self, key: Uuid) -> ImageStoreResult<()> {
+            self.client.delete_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .send()
+                .await.map_err(aws_sdk_s3::Error::from)?;
+
+            Ok(())
+        }
+
+        async fn head(&self, key: Uuid) -> ImageStoreResult<Option<ImageMetadata>> {
+            let result = self.client.head_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .send()
+                .await;
+
+            match result {
+                Err(err) if err.as_service_error().is_some_and(|e| e.is_not_found()) => {
+                    Ok(None)
+                }
+                Err(err) => Err(aws_sdk_s3::Error::from(err).into()),
+                Ok(result) => Ok(Some(ImageMetadata {
+                    content_type: result.content_type
+                        .unwrap_or(DEFAULT_CONTENT_TYPE.to_string()),
+                    size: result.content_length.unwrap_or_default().max(0) as u64,
+                    etag: result.e_tag,
+                })),
+            }
+        }
+
+        async fn get_stream(&self, key: Uuid, range: Option<ByteRange>) -> ImageStoreResult<Option<ImageStream>> {
+            let result = self.client.get_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .set_range(range.map(|range| format!("bytes={}-{}", range.start, range.end)))
+                .send()
+                .await;
+
+            let result = match result {
+                Err(err) if err.as_service_error().is_some_and(|e| e.is_no_such_key()) => {
+                    return Ok(None);
+                }
+                Err(err) => return Err(aws_sdk_s3::Error::from(err).into()),
+                Ok(result) => result,
+            };
+
+            let size = match range {
+                // Content-Range looks like "bytes 0-99/1234"
+                Some(_) => result.content_range()
+                    .and_then(|content_range| content_range.rsplit_once('/'))
+                    .and_then(|(_, size)| size.parse().ok())
+                    .unwrap_or_default(),
+                None => result.content_length.unwrap_or_default().max(0) as u64,
+            };
+
+            let metadata = ImageMetadata {
+                content_type: result.content_type
+                    .unwrap_or(DEFAULT_CONTENT_TYPE.to_string()),
+                size,
+                etag: result.e_tag,
+            };
+
+            let body = futures::stream::unfold(result.body, |mut body| async move {
+                body.next().await
+                    .map(|chunk| (chunk.map_err(Into::into), body))
+            });
+
+            Ok(Some(ImageStream { metadata, range, body: body.boxed() }))
+        }
+
+        async fn list(&self) -> ImageStoreResult<Vec<Uuid>> {
+            let mut pages = self.client.list_objects_v2()
+                .bucket(&self.bucket)
+                .into_paginator()
+                .send();
+
+            let mut keys = Vec::new();
+            while let Some(page) = pages.next().await {
+                let page = page.map_err(aws_sdk_s3::Error::from)?;
+                keys.extend(page.contents()
+                    .iter()
+                    .filter_map(|object| object.key())
+                    .filter_map(|key| key.parse::<Uuid>().ok()));
+            }
+
+            Ok(keys)
+        }
+
+        async fn presign_put(&self, key: Uuid, content_type: &str, expires_in: Duration)
+            -> ImageStoreResult<String>
+        {
+            let request = self.client.put_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .content_type(content_type)
+                .presigned(PresigningConfig::expires_in(expires_in)?)
+                .await.map_err(aws_sdk_s3::Error::from)?;
+
+            Ok(request.uri().to_string())
+        }
+    }
+}
+
+pub mod filesystem {
+    use std::{io::ErrorKind, path::PathBuf};
+
+    use tokio::fs;
+
+    use super::*;
+
+    /// Stores every image as `<key>` in a directory, with its content type
+    /// next to it in `<key>.content-type`.
+    #[derive(Debug, Clone)]
+    pub struct FilesystemImageStore {
+        directory: PathBuf,
+    }
+
+    impl FilesystemImageStore {
+        /// Creates the directory if it doesn't exist yet.
+        pub async fn new(directory: impl Into<PathBuf>) -> std::io::Result<Self> {
+            let directory = directory.into();
+            fs::create_dir_all(&directory).await?;
+            Ok(Self { directory })
+        }
+
+        fn data_path(&self, key: Uuid) -> PathBuf {
+            self.directory.join(key.to_string())
+        }
+
+        fn content_type_path(&self, key: Uuid) -> PathBuf {
+            self.directory.join(format!("{key}.content-type"))
+        }
+
+        async fn read_content_type(&self, key: Uuid) -> ImageStoreResult<String> {
+            match fs::read_to_string(self.content_type_path(key)).await {
+                Ok(content_type) => Ok(content_type),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(DEFAULT_CONTENT_TYPE.to_string()),
+                Err(err) => Err(err.into()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ImageStore for FilesystemImageStore {
+        async fn put(&self, key: Uuid, data: Bytes, content_type: &str) -> ImageStoreResult<()> {
+            fs::write(self.data_path(key), data).await?;
+            fs::write(self.content_type_path(key), content_type).await?;
+            Ok(())
+        }
+
+        async fn get(&self, key: Uuid) -> ImageStoreResult<Option<StoredImage>> {
+            let data = match fs::read(self.data_path(key)).await {
+                Ok(data) => Bytes::from(data),
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            let content_type = self.read_content_type(key).await?;
+
+            Ok(Some(StoredImage { content_type, data }))
+        }
+
+        async fn delete(&self, key: Uuid) -> ImageStoreResult<()> {
+            for path in [self.data_path(key), self.content_type_path(key)] {
+                match fs::remove_file(path).await {
+                    Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+
+        async fn head(&self, key: Uuid) -> ImageStoreResult<Option<ImageMetadata>> {
+            let metadata = match fs::metadata(self.data_path(key)).await {
+                Ok(metadata) => metadata,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            let size = metadata.len();
+            let modified = metadata.modified()?
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+
+            let content_type = self.read_content_type(key).await?;
+
+            Ok(Some(ImageMetadata {
+                content_type,
+                size,
+                etag: Some(format!("\"{size:x}-{modified:x}\"")),
+            }))
+        }
+
+        async fn list(&self) -> ImageStoreResult<Vec<Uuid>> {
+            let mut entries = fs::read_dir(&self.directory).await?;
+
+            let mut keys = Vec::new();
+            while let Some(entry) = entries.next_entry().await? {
+                if let Some(key) = entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+                    keys.push(key);
+                }
+            }
+
+            Ok(keys)
+        }
+    }
+}
+
+pub mod memory {
+    use std::{collections::HashMap, hash::{DefaultHasher, Hash, Hasher}, sync::Mutex};
+
+    use super::*;
+
+    /// Keeps all images in memory, only useful for tests and local dev.
+    #[derive(Debug, Default)]
+    pub struct MemoryImageStore {
+        images: Mutex<HashMap<Uuid, StoredImage>>,
+    }
+
+    #[async_trait]
+    impl ImageStore for MemoryImageStore {
+        async fn put(&self, key: Uuid, data: Bytes, content_type: &str) -> ImageStoreResult<()> {
+            let image = StoredImage { content_type: content_type.to_string(), data };
+            self.images.lock().unwrap().insert(key, image);
+            Ok(())
+        }
+
+        async fn get(&self, key: Uuid) -> ImageStoreResult<Option<StoredImage>> {
+            Ok(self.images.lock().unwrap().get(&key).cloned())
+        }
+
+        async fn delete(&self, key: Uuid) -> ImageStoreResult<()> {
+            self.images.lock().unwrap().remove(&key);
+            Ok(())
+        }
+
+        async fn head(&self, key: Uuid) -> ImageStoreResult<Option<ImageMetadata>> {
+            let images = self.images.lock().unwrap();
+            Ok(images.get(&key).map(|image| {
+                let mut hasher = DefaultHasher::new();
+                image.data.hash(&mut hasher);
+
+                ImageMetadata {
+                    content_type: image.content_type.clone(),
+                    size: image.data.len() as u64,
+                    etag: Some(format!("\"{:x}\"", hasher.finish())),
+                }
+            }))
+        }
+
+        async fn list(&self) -> ImageStoreResult<Vec<Uuid>> {
+            Ok(self.images.lock().unwrap().keys().copied().collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ByteRange;
+
+    #[test]
+    fn resolve_byte_ranges() {
+        assert_eq!(ByteRange::resolve("0-99", 1000), Some(ByteRange { start: 0, end: 99 }));
+        assert_eq!(ByteRange::resolve("900-", 1000), Some(ByteRange { start: 900, end: 999 }));
+        assert_eq!(ByteRange::resolve("-100", 1000), Some(ByteRange { start: 900, end: 999 }));
+        assert_eq!(ByteRange::resolve("-5000", 1000), Some(ByteRange { start: 0, end: 999 }));
+        assert_eq!(ByteRange::resolve("500-5000", 1000), Some(ByteRange { start: 500, end: 999 }));
+
+        assert_eq!(ByteRange::resolve("1000-", 1000), None);
+        assert_eq!(ByteRange::resolve("99-0", 1000), None);
+        assert_eq!(ByteRange::resolve("-0", 1000), None);
+        assert_eq!(ByteRange::resolve("0-1,5-6", 1000), None);
+        assert_eq!(ByteRange::resolve("0-", 0), None);
+    }
+
+    #[test]
+    fn clamp_byte_ranges() {
+        let range = ByteRange { start: 100, end: 199 };
+        assert_eq!(range.clamp(1000), Some(range));
+        assert_eq!(range.clamp(150), Some(ByteRange { start: 100, end: 149 }));
+        assert_eq!(range.clamp(100), None);
+        assert_eq!(range.clamp(0), None);
+    }
+}
@@ -1,28 +1,97 @@
-use std::fmt::Debug;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use diesel::PgConnection;
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl, SelectableHelper};
+use itertools::Itertools;
 use postgis_diesel::types::Point;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use uuid::Uuid;
 
-use crate::{config::AppConfig, models::Plant};
+use crate::{config::AppConfig, models::{InsertPlant, Organ, Plant, TaxonRank}};
 
+/// Something that can tell which plant is on pictures. Implementations can be
+/// combined, see `chain::ChainRecogniser` and `cached::CachedRecogniser`.
 #[async_trait]
-pub trait PlantRecogniser: Clone {
-    type E: Debug;
-
-    fn new(config: &AppConfig) -> Self;
+pub trait PlantRecogniser: Send + Sync {
+    /// Used in logs.
+    fn name(&self) -> &'static str;
 
     /// Returns all plants recognised, from best to worst match.
     /// Return 10 plants maximum. All plants returned must be in the
     /// database with those exact detailis (this method can insert them
     /// into the db incase of missing plants).
-    async fn analyze_plant(&self, db: &mut PgConnection, info: &PlantRecognitionInfo)
-        -> Result<Vec<RankedPlant>, Self::E>;
+    /// Only lock the db while using it, never during network requests.
+    async fn analyze_plant(&self, db: &Mutex<PgConnection>, info: &PlantRecognitionInfo)
+        -> Result<Vec<RankedPlant>, RecognitionError>;
+
+    /// Requests left for today, None if unknown or unlimited.
+    fn remaining_requests(&self) -> Option<i64> {
+        None
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecognitionError {
+    #[error("No species matched the pictures")]
+    NoMatch,
+    #[error("Daily recognition quota exceeded")]
+    QuotaExceeded,
+    #[error("The pictures couldn't be analyzed: {0}")]
+    InvalidPictures(String),
+    #[error("Recognition service unavailable: {0}")]
+    Unavailable(String),
+    #[error("diesel/db error: {0}")]
+    Db(#[from] diesel::result::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecogniserKind {
+    PlantNet,
+    Mock,
+}
+
+impl std::str::FromStr for RecogniserKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim() {
+            "plantnet" => Ok(RecogniserKind::PlantNet),
+            "mock" => Ok(RecogniserKind::Mock),
+            name => Err(format!("Unknown plant recogniser {name}")),
+        }
+    }
+}
+
+/// Builds the configured recognisers, chained if there are several and
+/// cached unless the cache ttl is 0.
+pub fn from_config(config: &AppConfig) -> Arc<dyn PlantRecogniser> {
+    let mut recognisers = config.plant_recognisers().into_iter()
+        .map(|kind| -> Arc<dyn PlantRecogniser> {
+            match kind {
+                RecogniserKind::PlantNet => Arc::new(plantnet::PlantNetRecogniser::from_config(config)),
+                RecogniserKind::Mock => Arc::new(mock::MockRecogniser),
+            }
+        })
+        .collect_vec();
+
+    let recogniser = if recognisers.len() == 1 {
+        recognisers.remove(0)
+    } else {
+        Arc::new(chain::ChainRecogniser::new(recognisers))
+    };
+
+    let ttl = config.recognition_cache_ttl();
+    if ttl.is_zero() {
+        recogniser
+    } else {
+        Arc::new(cached::CachedRecogniser::new(recogniser, ttl))
+    }
 }
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct RankedPlant {
     pub plant: Plant,
     pub score: f32,
@@ -34,128 +103,385 @@ impl RankedPlant {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecognitionImage {
+    pub id: uuid::Uuid,
+    pub data: Bytes,
+    /// sha256 of `data`, see `image_analysis::content_hash`
+    pub content_hash: String,
+    pub organ: Organ,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct PlantRecognitionInfo {
-    // image data + filename
-    pub images: Vec<(Bytes, String)>,
+    pub images: Vec<RecognitionImage>,
+    /// Where the plant is, rounded.
     pub location: Option<Point>,
+    /// Roughly where the user is, used to narrow down which plants are likely.
+    pub region: Option<Point>,
+    /// ISO 639-1 code, used for the common names.
+    pub language: String,
+    /// Ignore cached results, e.g. when an admin re-runs a recognition.
+    pub refresh: bool,
+}
+
+impl PlantRecognitionInfo {
+    /// sha256 over the sorted content hashes with their organs and the rest of
+    /// the info, so the order the pictures were sent in doesn't matter.
+    pub fn cache_key(&self) -> String {
+        let mut hasher = Sha256::new();
+
+        let pictures = self.images.iter()
+            .map(|image| (&image.content_hash, image.organ.as_str()))
+            .sorted();
+
+        for (hash, organ) in pictures {
+            hasher.update(format!("{hash}:{organ}\n"));
+        }
+
+        if let Some(location) = self.location {
+            hasher.update(format!("location={},{}\n", location.x, location.y));
+        }
+
+        if let Some(region) = self.region {
+            hasher.update(format!("region={},{}\n", region.x, region.y));
+        }
+
+        hasher.update(format!("language={}", self.language));
+
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Loads the plant with the same powo id, or inserts it. Plants that were
+/// stored without a genus get the plant's genus.
+fn insert_or_load_plant(db: &mut PgConnection, plant: InsertPlant) -> Result<Plant, diesel::result::Error> {
+    use crate::schema::plants;
+
+    let plant_in_db = plants::table.filter(plants::powo_id.eq(&plant.powo_id))
+        .select(Plant::as_select())
+        .get_result(db).optional()?;
+
+    match plant_in_db {
+        Some(plant_in_db) if plant_in_db.genus.is_none() && plant.genus.is_some() => {
+            diesel::update(plants::table.find(plant_in_db.id))
+                .set(plants::genus.eq(plant.genus))
+                .returning(Plant::as_returning())
+                .get_result(db)
+        }
+        Some(plant_in_db) => Ok(plant_in_db),
+        None => plant.insert_into(plants::table)
+            .returning(Plant::as_returning())
+            .get_result(db),
+    }
+}
+
+/// Stores the plant's common names in the language (ISO 639-1). The first
+/// one is shown in that language, unless the plant already has a name for it.
+pub(super) fn insert_plant_names(db: &mut PgConnection, plant: Uuid, lang: &str, names: &[String]) -> Result<(), diesel::result::Error> {
+    use crate::schema::plant_names;
+
+    let names: Vec<String> = names.iter()
+        .map(|name| name.trim().chars().take(127).collect::<String>()) // plant_names.name is a VARCHAR(127)
+        .filter(|name| !name.is_empty())
+        .unique()
+        .collect();
+
+    let Some(first_name) = names.first() else {
+        return Ok(());
+    };
+
+    let has_primary: bool = diesel::select(diesel::dsl::exists(plant_names::table
+        .filter(plant_names::plant_id.eq(plant))
+        .filter(plant_names::lang.eq(lang))
+        .filter(plant_names::is_primary)))
+        .get_result(db)?;
+
+    let insert_names: Vec<_> = names.iter()
+        .map(|name| (plant_names::plant_id.eq(plant), plant_names::lang.eq(lang), plant_names::name.eq(name)))
+        .collect();
+
+    diesel::insert_into(plant_names::table)
+        .values(&insert_names)
+        .on_conflict_do_nothing()
+        .execute(db)?;
+
+    if !has_primary {
+        diesel::update(plant_names::table.find((plant, lang, first_name)))
+            .set(plant_names::is_primary.eq(true))
+            .execute(db)?;
+    }
+
+    Ok(())
+}
+
+/// Loads the taxon with the rank and name, or inserts it. A missing parent
+/// is filled in if we know it now.
+fn insert_or_load_taxon(db: &mut PgConnection, rank: TaxonRank, name: &str, parent: Option<Uuid>) -> Result<Uuid, diesel::result::Error> {
+    use crate::schema::taxa;
+
+    let taxon_in_db: Option<(Uuid, Option<Uuid>)> = taxa::table
+        .filter(taxa::rank.eq(rank))
+        .filter(taxa::name.eq(name))
+        .select((taxa::id, taxa::parent))
+        .get_result(db).optional()?;
+
+    match taxon_in_db {
+        Some((id, None)) if parent.is_some() => {
+            diesel::update(taxa::table.find(id))
+                .set(taxa::parent.eq(parent))
+                .execute(db)?;
+            Ok(id)
+        }
+        Some((id, _)) => Ok(id),
+        None => diesel::insert_into(taxa::table)
+            .values((taxa::rank.eq(rank), taxa::name.eq(name), taxa::parent.eq(parent)))
+            .returning(taxa::id)
+            .get_result(db),
+    }
+}
+
+/// The genus of a plant in the family, both are stored if they're new.
+pub(super) fn insert_or_load_genus(db: &mut PgConnection, genus: &str, family: Option<&str>) -> Result<Uuid, diesel::result::Error> {
+    let family = family
+        .map(|family| insert_or_load_taxon(db, TaxonRank::Family, family, None))
+        .transpose()?;
+
+    insert_or_load_taxon(db, TaxonRank::Genus, genus, family)
 }
 
 pub mod plantnet {
-    use std::sync::Arc;
+    use std::sync::atomic::{AtomicI64, Ordering};
 
-    use axum::async_trait;
-    use diesel::{ExpressionMethods, Insertable, OptionalExtension, PgConnection, SelectableHelper};
-    use reqwest::{multipart::{Form, Part}, Url};
+    use reqwest::{multipart::{Form, Part}, StatusCode, Url};
     use serde::Deserialize;
-
-    use crate::{config::AppConfig, models::{InsertPlant, Plant}};
+    use tracing::{debug, warn};
 
     use super::*;
 
+    /// Below this many remaining requests for today, every request logs a warning.
+    const LOW_QUOTA_WARNING: i64 = 50;
+
     #[derive(Debug, Clone)]
     pub struct PlantNetRecogniser {
         http_client: Arc<reqwest::Client>,
         base_url: Url,
         apikey: String,
+        /// Identification requests left for today, as last reported by plantnet.
+        /// Negative while unknown.
+        remaining_requests: Arc<AtomicI64>,
     }
 
     #[derive(Debug, thiserror::Error)]
     pub enum PlantNetError {
+        #[error("No species matched the pictures")]
+        SpeciesNotFound,
+        #[error("Plantnet daily quota exceeded")]
+        QuotaExceeded,
+        #[error("Plantnet rejected the api key")]
+        Unauthorized,
+        #[error("Plantnet rejected the request: {0}")]
+        BadRequest(String),
+        #[error("Unexpected response from plantnet: {status} {message}")]
+        UnexpectedStatus { status: StatusCode, message: String },
         #[error("Reqwest error: {0}")]
         Reqwest(#[from] reqwest::Error),
-        #[error("diesel/db error: {0}")]
-        Diesel(#[from] diesel::result::Error),
+    }
+
+    impl From<PlantNetError> for RecognitionError {
+        fn from(err: PlantNetError) -> Self {
+            match err {
+                PlantNetError::SpeciesNotFound => RecognitionError::NoMatch,
+                PlantNetError::QuotaExceeded => RecognitionError::QuotaExceeded,
+                PlantNetError::BadRequest(message) => RecognitionError::InvalidPictures(message),
+                err => RecognitionError::Unavailable(err.to_string()),
+            }
+        }
     }
 
     impl PlantNetRecogniser {
+        /// Only allows plain http if the base url isn't https, for tests.
         pub fn from_parts(base_url: Url, apikey: String) -> Self {
             let http_client = reqwest::Client::builder()
-                .https_only(true)
+                .https_only(base_url.scheme() == "https")
                 .build()
                 .expect("failed to built plantnet reqwest client");
 
             let http_client = Arc::new(http_client);
 
-            Self { http_client, base_url, apikey }
-        }
-    }
+            let remaining_requests = Arc::new(AtomicI64::new(-1));
 
-    #[async_trait]
-    impl PlantRecogniser for PlantNetRecogniser {
-        type E = PlantNetError;
+            Self { http_client, base_url, apikey, remaining_requests }
+        }
 
-        fn new(config: &AppConfig) -> Self {
+        pub fn from_config(config: &AppConfig) -> Self {
             let base_url = Url::parse(config.plantnet_api_url())
                 .expect("invalid plantnet base url");
 
-            PlantNetRecogniser::from_parts(
-                base_url,
-                config.plantnet_api_key().to_string()
-            )
+            let apikey = config.plantnet_api_key()
+                .expect("plantnet_api_key is required for the plantnet recogniser");
+
+            PlantNetRecogniser::from_parts(base_url, apikey.to_string())
         }
 
-        async fn analyze_plant(&self, db: &mut PgConnection, info: &PlantRecognitionInfo) -> Result<Vec<RankedPlant>, Self::E> {
-            let url = self.base_url.join("identity/all").unwrap();
+        /// Asks plantnet, without touching the database.
+        async fn identify(&self, info: &PlantRecognitionInfo) -> Result<RecogniseResponse, PlantNetError> {
+            let project = project_for_region(info.region.as_ref());
+            let url = self.base_url.join(&format!("identify/{project}")).unwrap();
 
             let mut body = Form::new();
 
-            for (image, image_name) in &info.images {
-                let part = Part::stream(image.clone()).file_name(image_name.clone());
-                body = body.part("images", part);
+            // plantnet matches organs to images by their order
+            for image in &info.images {
+                let part = Part::stream(image.data.clone()).file_name(image.id.to_string());
+                body = body
+                    .part("images", part)
+                    .text("organs", image.organ.as_str());
+            }
+
+            let mut query = vec![
+                ("nb-results", "10".to_string()),
+                ("lang", info.language.clone()),
+                ("api-key", self.apikey.clone()),
+            ];
+
+            if let Some(location) = info.location {
+                query.push(("lat", location.y.to_string()));
+                query.push(("lon", location.x.to_string()));
             }
 
-            let response: RecogniseResponse = self.http_client.post(url)
-                .query(&[
-                    ("nb-results", "10"),
-                    ("lang", "en"),
-                    ("api-key", &self.apikey)
-                    ])
+            let response = self.http_client.post(url)
+                .query(&query)
                 .multipart(body)
-                .send().await?
-                .json().await?;
+                .send().await?;
+
+            let status = response.status();
+
+            if !status.is_success() {
+                let message = response.json::<ErrorResponse>().await
+                    .map(|error| error.message)
+                    .unwrap_or_default();
+
+                return Err(match status {
+                    StatusCode::NOT_FOUND => PlantNetError::SpeciesNotFound,
+                    StatusCode::TOO_MANY_REQUESTS => {
+                        self.remaining_requests.store(0, Ordering::Relaxed);
+                        PlantNetError::QuotaExceeded
+                    }
+                    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => PlantNetError::Unauthorized,
+                    StatusCode::BAD_REQUEST => PlantNetError::BadRequest(message),
+                    status => PlantNetError::UnexpectedStatus { status, message },
+                });
+            }
+
+            let response: RecogniseResponse = response.json().await?;
+
+            if let Some(remaining) = response.remaining_identification_requests {
+                self.remaining_requests.store(remaining, Ordering::Relaxed);
+
+                if remaining < LOW_QUOTA_WARNING {
+                    warn!(remaining, "Plantnet quota is running low");
+                } else {
+                    debug!(remaining, "Plantnet quota left");
+                }
+            }
+
+            Ok(response)
+        }
+    }
+
+    #[async_trait]
+    impl PlantRecogniser for PlantNetRecogniser {
+        fn name(&self) -> &'static str {
+            "plantnet"
+        }
+
+        async fn analyze_plant(&self, db: &Mutex<PgConnection>, info: &PlantRecognitionInfo) -> Result<Vec<RankedPlant>, RecognitionError> {
+            let response = self.identify(info).await?;
+
+            let mut db = db.lock().await;
 
             let mut plants = Vec::new();
             for plant in response.results {
-                let db_plant = insert_or_load_plant(db, &plant)?;
+                // we can't store plants without a powo id
+                let Some(powo) = &plant.powo else {
+                    debug!(species = plant.species.scientific_name_without_author, "Skipping result without powo id");
+                    continue;
+                };
+
+                let genus = plant.species.genus.as_ref()
+                    .map(|genus| insert_or_load_genus(
+                        &mut db,
+                        &genus.scientific_name_without_author,
+                        plant.species.family.as_ref().map(|family| family.scientific_name_without_author.as_str()),
+                    ))
+                    .transpose()?;
+
+                let insert_plant = InsertPlant {
+                    powo_id: powo.id.clone(),
+                    gbif_id: plant.gbif.as_ref().and_then(|gbif| gbif.id.parse().ok()),
+                    human_name: display_name(&plant.species),
+                    species: plant.species.scientific_name_without_author.clone(),
+                    location: None,
+                    produces_fruit: None,
+                    description: "".to_string(),
+                    genus,
+                };
+
+                let db_plant = insert_or_load_plant(&mut db, insert_plant)?;
+                insert_plant_names(&mut db, db_plant.id, &info.language, &plant.species.common_names)?;
                 plants.push(RankedPlant::new(db_plant, plant.score));
             }
 
             Ok(plants)
         }
-    }
-
-    fn insert_or_load_plant(db: &mut PgConnection, res: &RecogniseResult) -> Result<Plant, diesel::result::Error> {
-        use crate::schema::plants::dsl::*;
-        use diesel::{QueryDsl, RunQueryDsl};
 
-        let plant_in_db = plants.filter(powo_id.eq(&res.powo.id))
-            .select(Plant::as_select())
-            .get_result(db).optional()?;
+        /// As last reported by plantnet, None if we haven't asked yet.
+        fn remaining_requests(&self) -> Option<i64> {
+            let remaining = self.remaining_requests.load(Ordering::Relaxed);
+            (remaining >= 0).then_some(remaining)
+        }
+    }
 
-        if let Some(plant_in_db) = plant_in_db {
-            Ok(plant_in_db)
-        } else {
-            let insert_plant = InsertPlant {
-                powo_id: res.powo.id.clone(),
-                gbif_id: res.gbif.id.parse().ok(),
-                human_name: res.species.common_names.first().unwrap().to_string(),
-                species: res.species.scientific_name_without_author.clone(),
-                location: None,
-                produces_fruit: None,
-                description: "".to_string(),
-            };
+    /// Regional floras, (project, min lon, min lat, max lon, max lat).
+    /// The first one containing the region is used.
+    const REGIONAL_PROJECTS: &[(&str, f64, f64, f64, f64)] = &[
+        ("weurope", -25.0, 34.0, 30.0, 72.0),
+        ("canada", -141.0, 49.0, -52.0, 84.0),
+        ("namerica", -170.0, 15.0, -50.0, 75.0),
+        ("brazil", -74.0, -34.0, -34.0, 5.0),
+    ];
+
+    /// Picks the plantnet project (flora) for the region, "all" if
+    /// there's no region or no regional flora for it.
+    pub fn project_for_region(region: Option<&Point>) -> &'static str {
+        let Some(region) = region else {
+            return "all";
+        };
+
+        REGIONAL_PROJECTS.iter()
+            .find(|(_, min_lon, min_lat, max_lon, max_lat)| {
+                (*min_lon..=*max_lon).contains(&region.x) && (*min_lat..=*max_lat).contains(&region.y)
+            })
+            .map(|(project, ..)| *project)
+            .unwrap_or("all")
+    }
 
-            insert_plant.insert_into(plants)
-                .returning(Plant::as_returning())
-                .get_result(db)
-        }
+    /// Species without a common name use the scientific name instead.
+    fn display_name(species: &RecognisedSpecies) -> String {
+        species.common_names.first()
+            .unwrap_or(&species.scientific_name_without_author)
+            .chars()
+            .take(63) // plants.human_name is a VARCHAR(63)
+            .collect()
     }
 
     #[derive(Deserialize, Debug)]
     #[serde(rename_all = "camelCase")]
     struct RecogniseResponse {
-        results: Vec<RecogniseResult>
+        results: Vec<RecogniseResult>,
+        #[serde(default)]
+        remaining_identification_requests: Option<i64>,
     }
 
     #[derive(Deserialize, Debug)]
@@ -163,8 +489,10 @@ pub mod plantnet {
     struct RecogniseResult {
         score: f32,
         species: RecognisedSpecies,
-        gbif: Id,
-        powo: Id,
+        #[serde(default)]
+        gbif: Option<Id>,
+        #[serde(default)]
+        powo: Option<Id>,
     }
 
     #[derive(Deserialize, Debug)]
@@ -177,6 +505,412 @@ pub mod plantnet {
     #[serde(rename_all = "camelCase")]
     struct RecognisedSpecies {
         scientific_name_without_author: String,
+        #[serde(default)]
         common_names: Vec<String>,
+        #[serde(default)]
+        genus: Option<RecognisedTaxon>,
+        #[serde(default)]
+        family: Option<RecognisedTaxon>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    struct RecognisedTaxon {
+        scientific_name_without_author: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct ErrorResponse {
+        message: String,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::Mutex;
+
+        use axum::{extract::{Path, RawQuery}, http::StatusCode, routing::post, Router};
+        use bytes::Bytes;
+        use tokio::net::TcpListener;
+
+        use crate::models::Organ;
+
+        use super::*;
+
+        /// Requests the mock server got, as (project, query string).
+        type SeenRequests = Arc<Mutex<Vec<(String, String)>>>;
+
+        /// Starts a fake plantnet api answering every identify request with
+        /// the fixture, returns its base url.
+        async fn mock_plantnet(status: StatusCode, fixture: &'static str) -> (Url, SeenRequests) {
+            let seen = SeenRequests::default();
+            let seen_by_handler = seen.clone();
+
+            let app = Router::new().route("/v2/identify/:project", post(
+                move |Path(project): Path<String>, RawQuery(query): RawQuery| async move {
+                    seen_by_handler.lock().unwrap().push((project, query.unwrap_or_default()));
+                    (status, [("content-type", "application/json")], fixture)
+                }
+            ));
+
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            (Url::parse(&format!("http://{address}/v2/")).unwrap(), seen)
+        }
+
+        fn info(location: Option<Point>) -> PlantRecognitionInfo {
+            PlantRecognitionInfo {
+                images: vec![RecognitionImage {
+                    id: uuid::Uuid::now_v7(),
+                    data: Bytes::from_static(b"not really a jpeg"),
+                    content_hash: "a".repeat(64),
+                    organ: Organ::Leaf,
+                }],
+                location,
+                region: location,
+                language: "de".to_string(),
+                refresh: false,
+            }
+        }
+
+        #[tokio::test]
+        async fn identify_parses_results() {
+            let (url, seen) = mock_plantnet(StatusCode::OK, include_str!("../../tests/fixtures/plantnet/identify.json")).await;
+            let recogniser = PlantNetRecogniser::from_parts(url, "key".to_string());
+
+            assert_eq!(recogniser.remaining_requests(), None);
+
+            let response = recogniser.identify(&info(Some(Point::new(9.2, 48.8, Some(4326))))).await.unwrap();
+
+            assert_eq!(response.results.len(), 3);
+            assert_eq!(display_name(&response.results[0].species), "Swiss cheese plant");
+            assert_eq!(response.results[0].species.genus.as_ref().unwrap().scientific_name_without_author, "Monstera");
+            assert_eq!(response.results[0].species.family.as_ref().unwrap().scientific_name_without_author, "Araceae");
+            // no common names
+            assert_eq!(display_name(&response.results[1].species), "Monstera adansonii");
+            assert!(response.results[2].powo.is_none());
+            assert_eq!(recogniser.remaining_requests(), Some(437));
+
+            let seen = seen.lock().unwrap();
+            let (project, query) = &seen[0];
+            assert_eq!(project, "weurope");
+            assert!(query.contains("lang=de"));
+            assert!(query.contains("lat=48.8"));
+            assert!(query.contains("lon=9.2"));
+        }
+
+        #[tokio::test]
+        async fn identify_maps_errors() {
+            let cases = [
+                (StatusCode::NOT_FOUND, include_str!("../../tests/fixtures/plantnet/not_found.json")),
+                (StatusCode::TOO_MANY_REQUESTS, include_str!("../../tests/fixtures/plantnet/quota_exceeded.json")),
+                (StatusCode::UNAUTHORIZED, include_str!("../../tests/fixtures/plantnet/unauthorized.json")),
+                (StatusCode::BAD_REQUEST, include_str!("../../tests/fixtures/plantnet/bad_request.json")),
+                (StatusCode::BAD_GATEWAY, ""),
+            ];
+
+            for (status, fixture) in cases {
+                let (url, _) = mock_plantnet(status, fixture).await;
+                let recogniser = PlantNetRecogniser::from_parts(url, "key".to_string());
+
+                let err = recogniser.identify(&info(None)).await.unwrap_err();
+
+                match (status, err) {
+                    (StatusCode::NOT_FOUND, PlantNetError::SpeciesNotFound) => {}
+                    (StatusCode::TOO_MANY_REQUESTS, PlantNetError::QuotaExceeded) => {
+                        assert_eq!(recogniser.remaining_requests(), Some(0));
+                    }
+                    (StatusCode::UNAUTHORIZED, PlantNetError::Unauthorized) => {}
+                    (StatusCode::BAD_REQUEST, PlantNetError::BadRequest(message)) => {
+                        assert!(message.starts_with("Unsupported file type"));
+                    }
+                    (StatusCode::BAD_GATEWAY, PlantNetError::UnexpectedStatus { status, .. }) => {
+                        assert_eq!(status, StatusCode::BAD_GATEWAY);
+                    }
+                    (status, err) => panic!("{status} was mapped to {err:?}"),
+                }
+            }
+        }
+
+        #[test]
+        fn project_depends_on_region() {
+            assert_eq!(project_for_region(None), "all");
+            assert_eq!(project_for_region(Some(&Point::new(9.2, 48.8, None))), "weurope");
+            assert_eq!(project_for_region(Some(&Point::new(-113.5, 53.5, None))), "canada");
+            assert_eq!(project_for_region(Some(&Point::new(-87.6, 41.9, None))), "namerica");
+            assert_eq!(project_for_region(Some(&Point::new(139.7, 35.7, None))), "all");
+        }
+    }
+}
+
+pub mod chain {
+    use tracing::warn;
+
+    use super::*;
+
+    /// Tries the recognisers in order, until one of them recognises the plant.
+    pub struct ChainRecogniser {
+        recognisers: Vec<Arc<dyn PlantRecogniser>>,
+    }
+
+    impl ChainRecogniser {
+        pub fn new(recognisers: Vec<Arc<dyn PlantRecogniser>>) -> Self {
+            assert!(!recognisers.is_empty(), "a recogniser chain needs at least one recogniser");
+            Self { recognisers }
+        }
+    }
+
+    #[async_trait]
+    impl PlantRecogniser for ChainRecogniser {
+        fn name(&self) -> &'static str {
+            "chain"
+        }
+
+        /// Returns the result of the last recogniser if none of them found a plant.
+        async fn analyze_plant(&self, db: &Mutex<PgConnection>, info: &PlantRecognitionInfo) -> Result<Vec<RankedPlant>, RecognitionError> {
+            let mut result = Err(RecognitionError::NoMatch);
+
+            for recogniser in &self.recognisers {
+                result = recogniser.analyze_plant(db, info).await;
+
+                match &result {
+                    Ok(plants) if !plants.is_empty() => return result,
+                    Ok(_) => {}
+                    // not the recogniser's fault, the next one would fail too
+                    Err(RecognitionError::Db(_)) => return result,
+                    Err(err) => warn!(recogniser = recogniser.name(), ?err, "Plant recogniser failed"),
+                }
+            }
+
+            result
+        }
+
+        /// The first recogniser that knows its quota
+        fn remaining_requests(&self) -> Option<i64> {
+            self.recognisers.iter().find_map(|recogniser| recogniser.remaining_requests())
+        }
+    }
+}
+
+pub mod cached {
+    use tracing::{debug, warn};
+    use uuid::Uuid;
+
+    use crate::{models::Recognition, schema::{plants, recognitions}};
+
+    use super::*;
+
+    /// Stores the results of the wrapped recogniser in the `recognitions` table,
+    /// and answers requests with the same cache key from there for `ttl`.
+    pub struct CachedRecogniser {
+        inner: Arc<dyn PlantRecogniser>,
+        ttl: chrono::TimeDelta,
+    }
+
+    impl CachedRecogniser {
+        pub fn new(inner: Arc<dyn PlantRecogniser>, ttl: chrono::TimeDelta) -> Self {
+            Self { inner, ttl }
+        }
+
+        /// Cached results with the plants reloaded, they might have been edited since.
+        fn load_cached(&self, db: &mut PgConnection, cache_key: &str) -> Result<Option<Vec<RankedPlant>>, RecognitionError> {
+            let now = chrono::Utc::now().naive_utc();
+
+            let results: Option<serde_json::Value> = recognitions::table
+                .filter(recognitions::cache_key.eq(cache_key))
+                .filter(recognitions::created_at.gt(now - self.ttl))
+                .select(recognitions::results)
+                .get_result(db).optional()?;
+
+            let Some(results) = results else {
+                return Ok(None);
+            };
+
+            let cached: Vec<RankedPlant> = match serde_json::from_value(results) {
+                Ok(cached) => cached,
+                Err(err) => {
+                    warn!(?err, cache_key, "Ignoring unreadable cached recognition");
+                    return Ok(None);
+                }
+            };
+
+            let current_plants: Vec<Plant> = plants::table
+                .filter(plants::id.eq_any(cached.iter().map(|ranked| ranked.plant.id)))
+                .select(Plant::as_select())
+                .load(db)?;
+
+            let ranked = cached.into_iter()
+                .filter_map(|ranked| {
+                    let plant = current_plants.iter().find(|plant| plant.id == ranked.plant.id)?;
+                    Some(RankedPlant::new(plant.clone(), ranked.score))
+                })
+                .collect();
+
+            Ok(Some(ranked))
+        }
+    }
+
+    #[async_trait]
+    impl PlantRecogniser for CachedRecogniser {
+        fn name(&self) -> &'static str {
+            self.inner.name()
+        }
+
+        async fn analyze_plant(&self, db: &Mutex<PgConnection>, info: &PlantRecognitionInfo) -> Result<Vec<RankedPlant>, RecognitionError> {
+            let cache_key = info.cache_key();
+
+            if !info.refresh {
+                let cached = self.load_cached(&mut *db.lock().await, &cache_key)?;
+
+                if let Some(cached) = cached {
+                    debug!(cache_key, "Using cached plant recognition");
+                    return Ok(cached);
+                }
+            }
+
+            let plants = self.inner.analyze_plant(db, info).await?;
+
+            let recognition = Recognition {
+                id: Uuid::new_v4(),
+                cache_key,
+                images: info.images.iter().map(|image| image.id).collect(),
+                location: info.location,
+                results: serde_json::to_value(&plants).expect("ranked plants are always valid json"),
+                created_at: chrono::Utc::now().naive_utc(),
+                organs: info.images.iter().map(|image| image.organ).collect(),
+                language: info.language.clone(),
+                region: info.region,
+            };
+
+            // an expired entry for the same pictures gets replaced
+            (&recognition).insert_into(recognitions::table)
+                .on_conflict(recognitions::cache_key)
+                .do_update()
+                .set((
+                    recognitions::images.eq(&recognition.images),
+                    recognitions::results.eq(&recognition.results),
+                    recognitions::created_at.eq(recognition.created_at),
+                ))
+                .execute(&mut *db.lock().await)?;
+
+            Ok(plants)
+        }
+
+        fn remaining_requests(&self) -> Option<i64> {
+            self.inner.remaining_requests()
+        }
+    }
+}
+
+pub mod mock {
+    use super::*;
+
+    /// (powo id, English name, German name, species, family), the ids are
+    /// made up so they can't clash with real plants.
+    const MOCK_PLANTS: &[(&str, &str, &str, &str, &str)] = &[
+        ("mock-1", "Swiss cheese plant", "Fensterblatt", "Monstera deliciosa", "Araceae"),
+        ("mock-2", "Rubber plant", "Gummibaum", "Ficus elastica", "Moraceae"),
+        ("mock-3", "Snake plant", "Bogenhanf", "Dracaena trifasciata", "Asparagaceae"),
+        ("mock-4", "Golden pothos", "Efeutute", "Epipremnum aureum", "Araceae"),
+        ("mock-5", "Peace lily", "Einblatt", "Spathiphyllum wallisii", "Araceae"),
+    ];
+
+    const MOCK_SCORES: &[f32] = &[0.82, 0.11, 0.04];
+
+    /// Recognises one of a few made up plants, always the same ones for the
+    /// same pictures. For tests and local development without a plantnet key.
+    #[derive(Debug, Clone, Default)]
+    pub struct MockRecogniser;
+
+    #[async_trait]
+    impl PlantRecogniser for MockRecogniser {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        async fn analyze_plant(&self, db: &Mutex<PgConnection>, info: &PlantRecognitionInfo) -> Result<Vec<RankedPlant>, RecognitionError> {
+            let Some(first_image) = info.images.first() else {
+                return Err(RecognitionError::NoMatch);
+            };
+
+            let first_byte = u8::from_str_radix(&first_image.content_hash[..2], 16).unwrap_or(0);
+            let offset = first_byte as usize % MOCK_PLANTS.len();
+
+            let mut db = db.lock().await;
+
+            let mut plants = Vec::new();
+            for (index, score) in MOCK_SCORES.iter().enumerate() {
+                let (powo_id, human_name, german_name, species, family) = MOCK_PLANTS[(offset + index) % MOCK_PLANTS.len()];
+
+                let genus = species.split(' ').next().unwrap_or(species);
+                let genus = insert_or_load_genus(&mut db, genus, Some(family))?;
+
+                let insert_plant = InsertPlant {
+                    powo_id: powo_id.to_string(),
+                    gbif_id: None,
+                    human_name: human_name.to_string(),
+                    species: species.to_string(),
+                    location: None,
+                    produces_fruit: None,
+                    description: "".to_string(),
+                    genus: Some(genus),
+                };
+
+                let plant = insert_or_load_plant(&mut db, insert_plant)?;
+                insert_plant_names(&mut db, plant.id, "en", &[human_name.to_string()])?;
+                insert_plant_names(&mut db, plant.id, "de", &[german_name.to_string()])?;
+
+                plants.push(RankedPlant::new(plant, *score));
+            }
+
+            Ok(plants)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn image(content_hash: &str, organ: Organ) -> RecognitionImage {
+        RecognitionImage {
+            id: Uuid::now_v7(),
+            data: Bytes::new(),
+            content_hash: content_hash.repeat(64),
+            organ,
+        }
+    }
+
+    #[test]
+    fn cache_key_ignores_picture_order() {
+        let info = PlantRecognitionInfo {
+            images: vec![image("a", Organ::Leaf), image("b", Organ::Flower)],
+            location: Some(Point::new(9.2, 48.8, Some(4326))),
+            region: None,
+            language: "en".to_string(),
+            refresh: false,
+        };
+
+        let key = info.cache_key();
+
+        let mut reordered = info.clone();
+        reordered.images.reverse();
+        assert_eq!(key, reordered.cache_key());
+
+        let swapped_organs = PlantRecognitionInfo {
+            images: vec![image("a", Organ::Flower), image("b", Organ::Leaf)],
+            ..info.clone()
+        };
+        assert_ne!(key, swapped_organs.cache_key());
+
+        let german = PlantRecognitionInfo { language: "de".to_string(), ..info.clone() };
+        assert_ne!(key, german.cache_key());
+
+        let no_location = PlantRecognitionInfo { location: None, ..info };
+        assert_ne!(key, no_location.cache_key());
     }
 }
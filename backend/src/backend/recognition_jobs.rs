@@ -0,0 +1,200 @@
+use std::{collections::HashMap, sync::Arc, time::{Duration, Instant}};
+
+use serde::Serialize;
+use tokio::sync::{mpsc, watch, Mutex};
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use super::{recognition::RecognitionError, Backend, BackendError, BackendResult, RecognitionRequest, RecognitionResult};
+
+/// Finished jobs are forgotten after this long.
+const FINISHED_JOB_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// Plant recognitions waiting for or being run by a worker, see `run_worker`.
+/// Jobs only live in memory, they are lost on restart.
+#[derive(Clone)]
+pub struct RecognitionJobs {
+    jobs: Arc<std::sync::Mutex<HashMap<Uuid, Job>>>,
+    queue: mpsc::Sender<QueuedJob>,
+    receiver: Arc<Mutex<mpsc::Receiver<QueuedJob>>>,
+}
+
+struct Job {
+    user: Option<Uuid>,
+    state: watch::Sender<RecognitionJobState>,
+    finished_at: Option<Instant>,
+}
+
+struct QueuedJob {
+    id: Uuid,
+    user: Option<Uuid>,
+    request: RecognitionRequest,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RecognitionJobState {
+    Queued,
+    Running,
+    Done { result: RecognitionResult },
+    Failed { reason: RecognitionFailure, message: String },
+}
+
+impl RecognitionJobState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, RecognitionJobState::Done { .. } | RecognitionJobState::Failed { .. })
+    }
+
+    fn failed(err: &BackendError) -> Self {
+        let (reason, message) = match err {
+            BackendError::ImageNotFound(uuid) => (RecognitionFailure::ImageNotFound, format!("Couldn't find image with uuid={uuid}")),
+            BackendError::PlantRecognition(RecognitionError::NoMatch) => (RecognitionFailure::NoMatch, "No plant was recognised on the pictures".to_string()),
+            BackendError::PlantRecognition(RecognitionError::QuotaExceeded) =>
+                (RecognitionFailure::QuotaExceeded, "Plant recognition is unavailable for today, try again tomorrow".to_string()),
+            BackendError::PlantRecognition(RecognitionError::InvalidPictures(message)) =>
+                (RecognitionFailure::InvalidPictures, format!("The pictures couldn't be analyzed: {message}")),
+            BackendError::PlantRecognition(RecognitionError::Unavailable(_)) =>
+                (RecognitionFailure::Unavailable, "Plant recognition is currently unavailable".to_string()),
+            _ => (RecognitionFailure::Internal, "Plant analysis failed".to_string()),
+        };
+
+        RecognitionJobState::Failed { reason, message }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecognitionFailure {
+    ImageNotFound,
+    NoMatch,
+    QuotaExceeded,
+    InvalidPictures,
+    Unavailable,
+    Internal,
+}
+
+impl RecognitionJobs {
+    /// At most `queue_size` jobs can wait for a worker.
+    pub fn new(queue_size: usize) -> Self {
+        let (queue, receiver) = mpsc::channel(queue_size);
+
+        Self {
+            jobs: Default::default(),
+            queue,
+            receiver: Arc::new(Mutex::new(receiver)),
+        }
+    }
+
+    /// Queues the recognition and returns the job id.
+    pub fn submit(&self, user: Option<Uuid>, request: RecognitionRequest) -> BackendResult<Uuid> {
+        let id = Uuid::new_v4();
+
+        let mut jobs = self.jobs.lock().unwrap();
+
+        jobs.retain(|_, job| job.finished_at.is_none_or(|finished_at| finished_at.elapsed() < FINISHED_JOB_RETENTION));
+
+        self.queue.try_send(QueuedJob { id, user, request })
+            .map_err(|_| BackendError::RecognitionQueueFull)?;
+
+        let (state, _) = watch::channel(RecognitionJobState::Queued);
+        jobs.insert(id, Job { user, state, finished_at: None });
+
+        Ok(id)
+    }
+
+    /// Jobs of other users are treated like they don't exist.
+    pub fn state(&self, id: Uuid, user: Option<Uuid>) -> Option<RecognitionJobState> {
+        self.subscribe(id, user)
+            .map(|receiver| receiver.borrow().clone())
+    }
+
+    /// Notifies about every state change of the job, until it's finished.
+    pub fn subscribe(&self, id: Uuid, user: Option<Uuid>) -> Option<watch::Receiver<RecognitionJobState>> {
+        let jobs = self.jobs.lock().unwrap();
+
+        jobs.get(&id)
+            .filter(|job| job.user.is_none() || job.user == user)
+            .map(|job| job.state.subscribe())
+    }
+
+    fn set_state(&self, id: Uuid, state: RecognitionJobState) {
+        let mut jobs = self.jobs.lock().unwrap();
+
+        if let Some(job) = jobs.get_mut(&id) {
+            if state.is_finished() {
+                job.finished_at = Some(Instant::now());
+            }
+            job.state.send_replace(state);
+        }
+    }
+}
+
+/// Runs queued recognitions, forever. Spawn several for parallel recognitions.
+pub async fn run_worker(backend: Backend) {
+    let jobs = backend.recognition_jobs.clone();
+
+    loop {
+        let next_job = jobs.receiver.lock().await.recv().await;
+
+        let Some(QueuedJob { id, user, request }) = next_job else {
+            warn!("Recognition job queue closed, stopping worker");
+            return;
+        };
+
+        debug!(?id, "Running recognition job");
+        jobs.set_state(id, RecognitionJobState::Running);
+
+        let state = match backend.recognise_plant(user, request).await {
+            Ok(result) => RecognitionJobState::Done { result },
+            Err(err) => {
+                match &err {
+                    BackendError::ImageNotFound(_) | BackendError::PlantRecognition(
+                        RecognitionError::NoMatch | RecognitionError::InvalidPictures(_)
+                    ) => debug!(?id, ?err, "Recognition job failed"),
+                    _ => error!(?id, ?err, "Recognition job failed"),
+                }
+                RecognitionJobState::failed(&err)
+            }
+        };
+
+        jobs.set_state(id, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::Organ;
+
+    use super::*;
+
+    fn request() -> RecognitionRequest {
+        RecognitionRequest {
+            pictures: vec![(Uuid::now_v7(), Organ::Leaf)],
+            location: None,
+            language: "en".to_string(),
+        }
+    }
+
+    #[test]
+    fn jobs_are_queued_per_user() {
+        let jobs = RecognitionJobs::new(2);
+        let user = Uuid::now_v7();
+        let other_user = Uuid::now_v7();
+
+        let job = jobs.submit(Some(user), request()).unwrap();
+        let anonymous_job = jobs.submit(None, request()).unwrap();
+        assert!(matches!(jobs.submit(Some(user), request()), Err(BackendError::RecognitionQueueFull)));
+
+        assert!(matches!(jobs.state(job, Some(user)), Some(RecognitionJobState::Queued)));
+        assert!(jobs.state(job, Some(other_user)).is_none());
+        assert!(jobs.state(anonymous_job, Some(other_user)).is_some());
+
+        let mut receiver = jobs.subscribe(job, Some(user)).unwrap();
+        jobs.set_state(job, RecognitionJobState::failed(&BackendError::PlantRecognition(RecognitionError::NoMatch)));
+
+        assert!(receiver.has_changed().unwrap());
+        let state = receiver.borrow_and_update().clone();
+        assert!(matches!(state, RecognitionJobState::Failed { reason: RecognitionFailure::NoMatch, .. }));
+        assert!(state.is_finished());
+    }
+}
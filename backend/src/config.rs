@@ -1,26 +1,47 @@
 use serde::Deserialize;
 use config::{Config, FileFormat};
 
+use crate::{backend::{image_store::ImageStoreKind, recognition::RecogniserKind}, models::UploadQuota};
+
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     base_url: String,
     database_url: String,
-    s3_endpoint: String,
-    s3_access_key: String,
-    s3_secret_key: String,
-    s3_images_bucket: String,
+    #[serde(default)]
+    image_store: ImageStoreKind,
+    image_store_path: Option<String>,
+    s3_endpoint: Option<String>,
+    s3_access_key: Option<String>,
+    s3_secret_key: Option<String>,
+    s3_images_bucket: Option<String>,
     auth_server_url: String,
     auth_admin_role: String,
+    auth_trusted_role: String,
     auth_client_id: String,
     redis_url: String,
-    plantnet_api_key: String,
+    plant_recognisers: String,
+    plantnet_api_key: Option<String>,
     plantnet_api_url: String,
+    upload_quota_max_bytes: i64,
+    upload_quota_max_images: i64,
+    upload_quota_max_per_hour: i64,
+    recognition_cache_ttl_days: i64,
+    recognition_workers: usize,
+    recognition_queue_size: usize,
 }
 
 impl AppConfig {
     /// Read config from env's and a config file
     pub fn new() -> AppConfig {
         Config::builder()
+            .set_default("upload_quota_max_bytes", 500 * 1024 * 1024).unwrap()
+            .set_default("upload_quota_max_images", 500).unwrap()
+            .set_default("upload_quota_max_per_hour", 60).unwrap()
+            .set_default("recognition_cache_ttl_days", 30).unwrap()
+            .set_default("plant_recognisers", "plantnet").unwrap()
+            .set_default("recognition_workers", 2).unwrap()
+            .set_default("recognition_queue_size", 100).unwrap()
+            .set_default("auth_trusted_role", "trusted").unwrap()
             .add_source(config::File::new("config", FileFormat::Toml).required(false))
             .add_source(config::Environment::with_prefix("PLANTS"))
             .build().expect("Building config went wrong")
@@ -35,20 +56,30 @@ impl AppConfig {
         &self.database_url
     }
 
-    pub fn s3_endpoint(&self) -> &str {
-        &self.s3_endpoint
+    /// Where uploaded images are stored, s3 by default
+    pub fn image_store(&self) -> ImageStoreKind {
+        self.image_store
+    }
+
+    /// Directory for the filesystem image store
+    pub fn image_store_path(&self) -> Option<&str> {
+        self.image_store_path.as_deref()
+    }
+
+    pub fn s3_endpoint(&self) -> Option<&str> {
+        self.s3_endpoint.as_deref()
     }
 
-    pub fn s3_access_key(&self) -> &str {
-        &self.s3_access_key
+    pub fn s3_access_key(&self) -> Option<&str> {
+        self.s3_access_key.as_deref()
     }
 
-    pub fn s3_secret_key(&self) -> &str {
-        &self.s3_secret_key
+    pub fn s3_secret_key(&self) -> Option<&str> {
+        self.s3_secret_key.as_deref()
     }
 
-    pub fn s3_images_bucket(&self) -> &str {
-        &self.s3_images_bucket
+    pub fn s3_images_bucket(&self) -> Option<&str> {
+        self.s3_images_bucket.as_deref()
     }
 
     pub fn auth_server_url(&self) -> &str {
@@ -59,6 +90,11 @@ impl AppConfig {
         &self.auth_admin_role
     }
 
+    /// Role of users that may propose changes to plant information, "trusted" by default
+    pub fn auth_trusted_role(&self) -> &str {
+        &self.auth_trusted_role
+    }
+
     pub fn auth_client_id(&self) -> &str {
         &self.auth_client_id
     }
@@ -67,14 +103,48 @@ impl AppConfig {
         &self.redis_url
     }
 
-    pub fn plantnet_api_key(&self) -> &str {
-        &self.plantnet_api_key
+    /// Plant recognisers to try in order, comma separated (plantnet, mock),
+    /// "plantnet" by default
+    pub fn plant_recognisers(&self) -> Vec<RecogniserKind> {
+        self.plant_recognisers.split(',')
+            .map(|name| name.parse().unwrap_or_else(|err| panic!("{err}")))
+            .collect()
+    }
+
+    /// Only required for the plantnet recogniser
+    pub fn plantnet_api_key(&self) -> Option<&str> {
+        self.plantnet_api_key.as_deref()
     }
 
     /// Plantnet api url, normally https://my-api.plantnet.org/v2/
     pub fn plantnet_api_url(&self) -> &str {
         &self.plantnet_api_url
     }
+
+    /// Upload quota for users without an override, 500MiB/500 images/60 per hour by default
+    pub fn default_upload_quota(&self) -> UploadQuota {
+        UploadQuota {
+            max_bytes: self.upload_quota_max_bytes,
+            max_images: self.upload_quota_max_images,
+            max_per_hour: self.upload_quota_max_per_hour,
+        }
+    }
+
+    /// How long plant recognition results are reused, 30 days by default.
+    /// 0 disables the cache.
+    pub fn recognition_cache_ttl(&self) -> chrono::TimeDelta {
+        chrono::TimeDelta::days(self.recognition_cache_ttl_days)
+    }
+
+    /// How many plant recognitions run in parallel, 2 by default
+    pub fn recognition_workers(&self) -> usize {
+        self.recognition_workers
+    }
+
+    /// How many plant recognitions can wait for a worker, 100 by default
+    pub fn recognition_queue_size(&self) -> usize {
+        self.recognition_queue_size
+    }
 }
 
 impl Default for AppConfig {
@@ -1,17 +1,27 @@
+use std::sync::Arc;
+
 use askama::DynTemplate;
 use axum::{
-    extract::{Path, State}, http::StatusCode, response::{IntoResponse, Redirect}, routing::get, Router
+    extract::{Path, Query, State}, http::{HeaderMap, StatusCode}, response::{IntoResponse, Redirect, Response}, routing::{get, post}, Form, Router
 };
 use axum_htmx::HxRequest;
+use chrono::NaiveDateTime;
 use axum_login::login_required;
+use itertools::Itertools;
 use axum_typed_multipart::{FieldData, TryFromMultipart, TypedMultipart};
-use tracing::error;
+use serde::{de::{DeserializeOwned, IntoDeserializer as _}, Deserialize};
+use tracing::{error, warn};
 use uuid::Uuid;
 
 use crate::{
-    auth::{AuthSession, AuthState},
-    backend::{Backend, BackendError},
-    models::{InsertListing, ListingType},
+    auth::{is_admin, is_trusted, AuthSession, AuthState},
+    backend::{recognition_jobs::RecognitionJobState, Backend, BackendError, CreatedListing, Identification, ListingFilter, RecognitionRequest, RegionBounds},
+    config::AppConfig,
+    models::{
+        DraftFields, InsertTradeRestriction, Listing, ListingWithPlaceholder, Organ, Plant, PlantCare,
+        parse_price, RestrictionLevel, TaxonRank,
+    },
+    rest::user_language,
     AppState, LOGIN_URL,
 };
 
@@ -23,11 +33,30 @@ pub fn router() -> Router<AppState> {
             "/listing/new",
             get(render_create_listing).post(create_listing),
         )
+        .route("/listing/new/suggestions", post(suggest_plants))
+        .route("/listing/new/suggestions/:job_id", get(render_plant_suggestions))
+        .route("/listing/drafts", get(render_drafts))
+        .route("/listing/drafts/save", post(autosave_draft))
+        .route("/listing/drafts/:id", get(resume_draft))
+        .route("/listing/drafts/:id/delete", post(delete_draft))
+        .route(
+            "/listing/:humanname/:id/identification",
+            post(vote_identification).delete(retract_identification_vote),
+        )
+        .route("/moderation/duplicates", get(render_suspected_duplicates))
+        .route("/moderation/duplicates/:id/dismiss", post(dismiss_suspected_duplicate))
+        .route("/moderation/plant-edits", get(render_plant_edits))
+        .route("/moderation/plant-edits/:id/:decision", post(review_plant_edit))
+        .route("/moderation/trade-restrictions", get(render_trade_restrictions).post(add_trade_restriction))
+        .route("/moderation/trade-restrictions/:id/remove", post(remove_trade_restriction))
+        .route("/plants/:id/edit", post(edit_plant_care))
         .route_layer(login_required!(AuthState, login_url = LOGIN_URL))
         .route(
             "/listing/:humanname/:id",
             get(show_listing).post(show_listing),
         )
+        .route("/plants/:id", get(show_plant))
+        .route("/taxa/:id", get(show_taxon))
         .route("/home", get(render_homepage))
         .route("/about", get(render_about))
         .route("/discover", get(render_discover))
@@ -89,6 +118,7 @@ async fn show_listing(
     auth_session: AuthSession,
     HxRequest(is_htmx): HxRequest,
     State(backend): State<Backend>,
+    headers: HeaderMap,
     Path((_human_name, id)): Path<(String, Uuid)>,
 ) -> impl IntoResponse {
     let listing = backend.get_listing(id).await;
@@ -97,101 +127,810 @@ async fn show_listing(
             error!(?id, ?err, "Error while getting listing");
             Box::new(templates::pages::Error::new("Internal server error"))
         }
-        Ok(Some(listing)) => Box::new(templates::pages::ShowListing { listing }),
+        Ok(Some(ListingWithPlaceholder { listing, thumbnail_placeholder })) => {
+            let viewer = auth_session.user.as_ref().map(|user| user.claims.user_id);
+
+            // the listing is still worth showing without it
+            let mut identification = match backend.get_identification(id, viewer).await {
+                Ok(identification) => identification,
+                Err(err) => {
+                    error!(?id, ?err, "Error while getting identification of listing");
+                    None
+                }
+            };
+
+            if let Some(identification) = &mut identification {
+                let language = user_language(&backend, &auth_session, &headers).await;
+                localize_identification(&backend, identification, &language).await;
+            }
+
+            let identification = identification.map(|identification| templates::pages::ListingIdentification {
+                listing: listing.clone(),
+                identification,
+                viewer,
+            });
+
+            let recommendations = match backend.get_listing_recommendations(&listing, viewer).await {
+                Ok(recommendations) => recommendations,
+                Err(err) => {
+                    error!(?id, ?err, "Error while getting recommendations for listing");
+                    Default::default()
+                }
+            };
+
+            // blocking ones keep the listing from being created, the author
+            // still has to know about the others, also for scheduled drafts
+            let restriction_warnings = match (listing.identified_plant, viewer) {
+                (Some(plant), Some(viewer)) if viewer == listing.author => {
+                    match backend.get_plant_restrictions(plant, Some(viewer)).await {
+                        Ok(restrictions) => restrictions.into_iter()
+                            .filter(|restriction| restriction.level == RestrictionLevel::Warn)
+                            .collect(),
+                        Err(err) => {
+                            error!(?id, ?err, "Error while getting trade restrictions of listing");
+                            Vec::new()
+                        }
+                    }
+                }
+                _ => Vec::new(),
+            };
+
+            Box::new(templates::pages::ShowListing {
+                listing,
+                thumbnail_placeholder,
+                identification,
+                recommendations,
+                restriction_warnings,
+            })
+        }
         Ok(None) => Box::new(templates::pages::Error::new("404 Couldn't find listing")),
     };
 
     render_htmx_page(is_htmx, None, auth_session, content)
 }
 
+async fn show_plant(
+    auth_session: AuthSession,
+    HxRequest(is_htmx): HxRequest,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let mut plant = match backend.get_plant(id).await {
+        Ok(Some(plant)) => plant,
+        Ok(None) => {
+            let page = templates::pages::Error::new("404 Couldn't find plant");
+            let rendered_page = render_htmx_page(is_htmx, None, auth_session, Box::new(page));
+            return (StatusCode::NOT_FOUND, rendered_page).into_response();
+        }
+        Err(err) => {
+            error!(?id, ?err, "Error while getting plant");
+            let page = templates::pages::Error::new("Internal server error");
+            return render_htmx_page(is_htmx, None, auth_session, Box::new(page)).into_response();
+        }
+    };
+
+    let filter = ListingFilter { plant: Some(id), ..Default::default() };
+
+    let mut similar = match backend.get_similar_plants(id).await {
+        Ok(similar) => similar,
+        Err(err) => {
+            error!(?id, ?err, "Error while getting similar plants");
+            Vec::new()
+        }
+    };
+
+    let language = user_language(&backend, &auth_session, &headers).await;
+    localize_plants(&backend, std::iter::once(&mut plant).chain(similar.iter_mut().map(|similar| &mut similar.plant)), &language).await;
+
+    let other_names = match backend.get_plant_names(id).await {
+        Ok(names) => names.into_iter()
+            .map(|name| name.name)
+            .filter(|name| *name != plant.human_name)
+            .unique()
+            .collect(),
+        Err(err) => {
+            error!(?id, ?err, "Error while getting names of plant");
+            Vec::new()
+        }
+    };
+
+    let taxa = match backend.get_plant_taxa(&plant).await {
+        Ok(taxa) => taxa,
+        Err(err) => {
+            error!(?id, ?err, "Error while getting taxonomy of plant");
+            Vec::new()
+        }
+    };
+
+    let page: Box<dyn DynTemplate> = match backend.search_listings(&filter).await {
+        Ok(listings) => {
+            let can_edit = is_trusted(&auth_session, &config);
+            Box::new(templates::pages::ShowPlant { plant, other_names, listings, similar, taxa, can_edit })
+        }
+        Err(err) => {
+            error!(?id, ?err, "Error while getting listings of plant");
+            Box::new(templates::pages::Error::new("Internal server error"))
+        }
+    };
+
+    render_htmx_page(is_htmx, None, auth_session, page).into_response()
+}
+
+/// A family or genus with the listings of all its plants.
+async fn show_taxon(
+    auth_session: AuthSession,
+    HxRequest(is_htmx): HxRequest,
+    State(backend): State<Backend>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let mut details = match backend.get_taxon(id).await {
+        Ok(Some(details)) => details,
+        Ok(None) => {
+            let page = templates::pages::Error::new("404 Couldn't find family or genus");
+            let rendered_page = render_htmx_page(is_htmx, None, auth_session, Box::new(page));
+            return (StatusCode::NOT_FOUND, rendered_page).into_response();
+        }
+        Err(err) => {
+            error!(?id, ?err, "Error while getting taxon");
+            let page = templates::pages::Error::new("Internal server error");
+            return render_htmx_page(is_htmx, None, auth_session, Box::new(page)).into_response();
+        }
+    };
+
+    let language = user_language(&backend, &auth_session, &headers).await;
+    localize_plants(&backend, &mut details.plants, &language).await;
+
+    let filter = ListingFilter { taxon: Some(id), ..Default::default() };
+
+    let page: Box<dyn DynTemplate> = match backend.search_listings(&filter).await {
+        Ok(listings) => Box::new(templates::pages::ShowTaxon { details, listings }),
+        Err(err) => {
+            error!(?id, ?err, "Error while getting listings of taxon");
+            Box::new(templates::pages::Error::new("Internal server error"))
+        }
+    };
+
+    render_htmx_page(is_htmx, None, auth_session, page).into_response()
+}
+
+/// Empty fields mean unknown, those don't change anything.
 #[derive(TryFromMultipart)]
-struct InsertListingBody {
-    pub title: String,
-    #[form_data(default)]
-    pub description: String,
-    pub listing_type: ListingType,
+struct PlantCareForm {
+    pub watering: Option<String>,
+    pub light: Option<String>,
+    pub season: Option<String>,
+    pub toxic_to_pets: Option<String>,
+    pub hardiness_zone: Option<String>,
+    pub propagation_methods: Vec<String>,
+}
+
+/// The choices of selects are the serialized enum variants, empty means none.
+fn parse_choice<T: DeserializeOwned>(value: Option<String>) -> Result<Option<T>, &'static str> {
+    value.filter(|value| !value.is_empty())
+        .map(|value| T::deserialize(value.as_str().into_deserializer())
+            .map_err(|_: serde::de::value::Error| "Invalid choice"))
+        .transpose()
+}
+
+/// Empty number inputs are None.
+fn parse_number<T: std::str::FromStr>(value: Option<String>, error: &'static str) -> Result<Option<T>, &'static str> {
+    value.filter(|value| !value.trim().is_empty())
+        .map(|value| value.trim().parse().map_err(|_| error))
+        .transpose()
+}
+
+impl PlantCareForm {
+    fn into_plant_care(self) -> Result<PlantCare, &'static str> {
+        let hardiness_zone = self.hardiness_zone
+            .filter(|zone| !zone.is_empty())
+            .map(|zone| zone.parse().map_err(|_| "The hardiness zone has to be a number"))
+            .transpose()?;
+
+        let toxic_to_pets = match self.toxic_to_pets.as_deref() {
+            None | Some("") => None,
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            Some(_) => return Err("Invalid choice"),
+        };
+
+        let propagation_methods = self.propagation_methods.into_iter()
+            .filter_map(|method| parse_choice(Some(method)).transpose())
+            .collect::<Result<_, _>>()?;
+
+        Ok(PlantCare {
+            watering: parse_choice(self.watering)?,
+            light: parse_choice(self.light)?,
+            hardiness_zone,
+            toxic_to_pets,
+            propagation_methods: Some(propagation_methods),
+            season: parse_choice(self.season)?,
+        })
+    }
+}
+
+async fn edit_plant_care(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(id): Path<Uuid>,
+    TypedMultipart(form): TypedMultipart<PlantCareForm>,
+) -> impl IntoResponse {
+    if !is_trusted(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let author = auth_session.user.as_ref().unwrap().claims.user_id;
+    let approved = is_admin(&auth_session, &config);
+
+    let plant = match backend.get_plant(id).await {
+        Ok(Some(plant)) => plant,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting plant");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let changes = match form.into_plant_care() {
+        Ok(care) => care.changes_to(&plant.care()),
+        Err(message) => return templates::pages::PlantEditResult { message, error: true }.into_response(),
+    };
+
+    let message = match backend.propose_plant_edit(id, author, &changes, approved).await {
+        Ok(Some(_)) if approved => "Saved",
+        Ok(Some(_)) => "Thanks! Your changes will show up once a moderator approved them.",
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(BackendError::InvalidPlantEdit(message)) => {
+            return templates::pages::PlantEditResult { message: &message, error: true }.into_response();
+        }
+        Err(err) => {
+            error!(?err, ?id, "Error while editing plant care information");
+            return templates::pages::PlantEditResult { message: "Internal server error", error: true }.into_response();
+        }
+    };
+
+    templates::pages::PlantEditResult { message, error: false }.into_response()
+}
+
+async fn render_plant_edits(
+    auth_session: AuthSession,
+    HxRequest(is_htmx): HxRequest,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        let page = templates::pages::Error::new("403 Only moderators can see this page");
+        let rendered_page = render_htmx_page(is_htmx, None, auth_session, Box::new(page));
+        return (StatusCode::FORBIDDEN, rendered_page).into_response();
+    }
+
+    let page: Box<dyn DynTemplate> = match backend.get_pending_plant_edits().await {
+        Err(err) => {
+            error!(?err, "Error while getting pending plant edits");
+            Box::new(templates::pages::Error::new("Internal server error"))
+        }
+        Ok(edits) => {
+            let edits = edits.into_iter()
+                .map(|(edit, plant)| {
+                    let changes = serde_json::from_value(edit.changes.clone()).unwrap_or_else(|err| {
+                        error!(?err, id = ?edit.id, "Plant edit has invalid changes");
+                        PlantCare::default()
+                    });
+                    (edit, plant, changes)
+                })
+                .collect();
+            Box::new(templates::pages::PlantEdits { edits })
+        }
+    };
+
+    render_htmx_page(is_htmx, None, auth_session, page).into_response()
+}
+
+async fn review_plant_edit(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path((id, decision)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let approve = match decision.as_str() {
+        "approve" => true,
+        "reject" => false,
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    let reviewer = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    match backend.review_plant_edit(id, reviewer, approve).await {
+        // the row gets replaced with nothing
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(err) => {
+            error!(?err, ?id, approve, "Error while reviewing plant edit");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct IdentificationVoteForm {
+    pub plant: Uuid,
+}
+
+async fn vote_identification(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    headers: HeaderMap,
+    Path((_human_name, id)): Path<(String, Uuid)>,
+    Form(form): Form<IdentificationVoteForm>,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    match backend.vote_identification(id, user, form.plant).await {
+        Ok(Some(listing)) => {
+            let language = user_language(&backend, &auth_session, &headers).await;
+            render_listing_identification(&backend, listing, user, &language).await
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err @ BackendError::TradeRestricted(_)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, err.listing_problem().unwrap_or_default()).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?id, "Error while voting for identification of listing");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn retract_identification_vote(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    headers: HeaderMap,
+    Path((_human_name, id)): Path<(String, Uuid)>,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    match backend.retract_identification_vote(id, user).await {
+        Ok(Some(listing)) => {
+            let language = user_language(&backend, &auth_session, &headers).await;
+            render_listing_identification(&backend, listing, user, &language).await
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while retracting identification vote");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn render_listing_identification(backend: &Backend, listing: Listing, viewer: Uuid, language: &str) -> Response {
+    match backend.get_identification(listing.id, Some(viewer)).await {
+        Ok(Some(mut identification)) => {
+            localize_identification(backend, &mut identification, language).await;
+            templates::pages::ListingIdentification { listing, identification, viewer: Some(viewer) }
+                .into_response()
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, id = ?listing.id, "Error while getting identification of listing");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Shows the plants' names in the language. They keep their names if that
+/// fails, the page is still usable with those.
+async fn localize_plants<'a>(backend: &Backend, plants: impl IntoIterator<Item = &'a mut Plant>, language: &str) {
+    if let Err(err) = backend.localize_plants(plants, language).await {
+        error!(?err, language, "Error while localizing plant names");
+    }
+}
+
+async fn localize_identification(backend: &Backend, identification: &mut Identification, language: &str) {
+    let plants = identification.plant.iter_mut()
+        .chain(identification.votes.iter_mut().map(|votes| &mut votes.plant));
+
+    localize_plants(backend, plants, language).await;
+}
+
+/// The create listing form, it gets saved to a draft before it's published.
+#[derive(TryFromMultipart)]
+struct ListingForm {
+    /// The draft the form was saved to before, empty the first time.
+    pub draft: Option<String>,
+    /// "draft" only saves the form, otherwise it gets published.
+    pub action: Option<String>,
+    /// Publishes the draft at this time (UTC) instead of right away.
+    pub publish_at: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub listing_type: Option<String>,
     #[form_data(limit = "10MiB")]
     pub pictures: Vec<FieldData<axum::body::Bytes>>,
+    /// Pictures already uploaded while the plant got recognised, or
+    /// with an earlier save of the draft. These replace `pictures`.
+    pub uploaded_pictures: Vec<Uuid>,
     #[form_data(default)]
     pub tradeable: bool,
+    /// Empty if the user didn't pick any of the suggested plants.
+    pub identified_plant: Option<String>,
+    /// Empty means a mature plant
+    pub propagation_form: Option<String>,
+    /// Empty means 1
+    pub quantity: Option<String>,
+    /// Empty if it doesn't come in a pot
+    pub pot_size_cm: Option<String>,
+    pub price_mode: Option<String>,
+    /// In the currency, e.g. 12.50
+    pub price: Option<String>,
+    pub currency: Option<String>,
+    #[form_data(default)]
+    pub price_negotiable: bool,
+    #[form_data(default)]
+    pub local_pickup: bool,
+    #[form_data(default)]
+    pub shipping: bool,
+    /// In the same currency as the price
+    pub shipping_cost: Option<String>,
+    #[form_data(default)]
+    pub meet_halfway: bool,
+    pub max_pickup_distance_km: Option<String>,
 }
 
-impl InsertListingBody {
-    pub fn into_insert_listing(self, author: Uuid, thumbnail: Uuid) -> InsertListing {
-        InsertListing {
-            title: self.title,
-            description: self.description,
-            author,
-            listing_type: self.listing_type,
+impl ListingForm {
+    /// Incomplete forms are fine, only what's filled in has to make sense.
+    fn into_draft_fields(self) -> Result<DraftFields, &'static str> {
+        let non_empty = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+
+        let identified_plant = self.identified_plant
+            .and_then(|plant| Uuid::parse_str(&plant).ok());
+
+        let currency = self.currency
+            .map(|currency| currency.trim().to_uppercase())
+            .filter(|currency| !currency.is_empty());
+        let price_minor = match (non_empty(self.price), &currency) {
+            (Some(price), Some(currency)) => Some(parse_price(&price, currency).ok_or("The price has to be an amount like 12.50")?),
+            (Some(_), None) => return Err("The price needs a currency"),
+            (None, _) => None,
+        };
+        let shipping_cost_minor = match (non_empty(self.shipping_cost), &currency) {
+            (Some(cost), Some(currency)) => Some(parse_price(&cost, currency).ok_or("The shipping cost has to be an amount like 4.90")?),
+            (Some(_), None) => return Err("The shipping cost needs a currency"),
+            (None, _) => None,
+        };
+
+        Ok(DraftFields {
+            title: non_empty(self.title),
+            description: non_empty(self.description),
+            listing_type: parse_choice(self.listing_type)?,
             tradeable: Some(self.tradeable),
-            thumbnail,
+            identified_plant,
+            propagation_form: parse_choice(self.propagation_form)?,
+            quantity: parse_number(self.quantity, "The quantity has to be a number")?,
+            pot_size_cm: parse_number(self.pot_size_cm, "The pot size has to be a number")?,
+            price_mode: parse_choice(self.price_mode)?,
+            // the currency input has a default, it only matters with a price
+            currency: currency.clone().filter(|_| price_minor.is_some()),
+            price_minor,
+            price_negotiable: Some(self.price_negotiable),
+            local_pickup: Some(self.local_pickup),
+            shipping: Some(self.shipping),
+            shipping_currency: currency.filter(|_| shipping_cost_minor.is_some()),
+            shipping_cost_minor,
+            meet_halfway: Some(self.meet_halfway),
+            max_pickup_distance_km: parse_number(self.max_pickup_distance_km, "The pickup distance has to be a number")?,
+        })
+    }
+}
+
+/// `datetime-local` inputs send e.g. "2026-10-18T14:30", empty means right away.
+fn parse_publish_at(value: Option<String>) -> Result<Option<NaiveDateTime>, &'static str> {
+    value.filter(|value| !value.is_empty())
+        .map(|value| NaiveDateTime::parse_from_str(&value, "%Y-%m-%dT%H:%M")
+            .map_err(|_| "The publishing time has to be a date and time"))
+        .transpose()
+}
+
+/// Saves the form to the draft, or to a new one if there is none yet.
+/// Returns the id of the draft, or the error to show.
+async fn save_listing_form(backend: &Backend, author: Uuid, draft: Option<Uuid>, mut form: ListingForm) -> Result<Uuid, String> {
+    // an empty file input still sends an empty file
+    let files: Vec<_> = std::mem::take(&mut form.pictures).into_iter()
+        .filter(|file| !file.contents.is_empty())
+        .collect();
+
+    let mut pictures = std::mem::take(&mut form.uploaded_pictures);
+    if pictures.is_empty() && !files.is_empty() {
+        pictures = upload_pictures(backend, author, &files).await?;
+    }
+
+    let fields = form.into_draft_fields()?;
+
+    let saved = match draft {
+        Some(id) => backend.save_draft(id, author, &fields).await
+            .map(|draft| draft.map(|draft| draft.id)),
+        None => backend.create_draft(author, &fields).await
+            .map(|draft| Some(draft.id)),
+    };
+    let id = match saved {
+        Ok(Some(id)) => id,
+        Ok(None) => return Err("The draft doesn't exist anymore".to_string()),
+        Err(BackendError::InvalidListing(message)) => return Err(message),
+        Err(err) => {
+            error!(?err, "Database error while saving draft");
+            return Err("Internal server error, try again later".to_string());
+        }
+    };
+
+    // the form only has pictures once they're uploaded
+    if !pictures.is_empty() {
+        match backend.set_draft_pictures(id, author, &pictures).await {
+            Ok(_) => {}
+            Err(BackendError::ImageNotFound(_)) => return Err("A picture doesn't exist anymore, upload it again".to_string()),
+            Err(err) => {
+                error!(?err, draft = ?id, "Database error while setting pictures of draft");
+                return Err("Internal server error, try again later".to_string());
+            }
         }
     }
+
+    Ok(id)
+}
+
+/// The create listing page, filled in from the draft if there is one.
+async fn listing_form_page(backend: &Backend, author: Uuid, draft: Option<Uuid>) -> templates::pages::CreateListing {
+    let draft = match draft {
+        Some(id) => backend.get_draft(id, author).await
+            .inspect_err(|err| error!(?err, draft = ?id, "Error while getting draft"))
+            .ok()
+            .flatten(),
+        None => None,
+    };
+
+    templates::pages::CreateListing { draft, ..templates::pages::CreateListing::new() }
 }
 
 async fn create_listing(
     auth_session: AuthSession,
     State(backend): State<Backend>,
-    TypedMultipart(body): TypedMultipart<InsertListingBody>,
+    TypedMultipart(mut form): TypedMultipart<ListingForm>,
 ) -> impl IntoResponse {
     let author = auth_session.user.as_ref().unwrap().claims.user_id;
 
-    if body.pictures.is_empty() {
-        let page = templates::pages::CreateListing::with_error("You need to upload at least one image");
+    let draft = form.draft.take().and_then(|draft| Uuid::parse_str(&draft).ok());
+    let save_only = form.action.take().as_deref() == Some("draft");
+    let publish_at = form.publish_at.take();
+
+    let draft = match save_listing_form(&backend, author, draft, form).await {
+        Ok(draft) => draft,
+        Err(error) => {
+            let page = templates::pages::CreateListing { error: Some(error), ..listing_form_page(&backend, author, draft).await };
+            return render_htmx_page(true, None, auth_session, Box::new(page)).into_response();
+        }
+    };
+
+    if save_only {
+        let page = templates::pages::CreateListing { message: Some("Draft saved".to_string()), ..listing_form_page(&backend, author, Some(draft)).await };
         return render_htmx_page(true, None, auth_session, Box::new(page)).into_response();
     }
 
-    for picture in &body.pictures {
+    let publish_at = match parse_publish_at(publish_at) {
+        Ok(publish_at) => publish_at,
+        Err(error) => {
+            let page = templates::pages::CreateListing { error: Some(error.to_string()), ..listing_form_page(&backend, author, Some(draft)).await };
+            return render_htmx_page(true, None, auth_session, Box::new(page)).into_response();
+        }
+    };
+
+    if publish_at.is_some() {
+        return match backend.schedule_draft(draft, author, publish_at).await {
+            Ok(_) => Redirect::to("/listing/drafts").into_response(),
+            Err(err) => {
+                error!(?err, ?draft, "Database error while scheduling draft");
+                let page = templates::pages::CreateListing::with_error("Internal server error, try again later");
+                render_htmx_page(true, None, auth_session, Box::new(page)).into_response()
+            }
+        };
+    }
+
+    let error = match backend.publish_draft(draft, author).await {
+        Ok(Some(CreatedListing { listing, .. })) => {
+            let id = listing.id;
+            let human_name = convert_title_to_human_url(listing.title);
+
+            return Redirect::permanent(&format!("/listing/{human_name}/{id}")).into_response();
+        }
+        Ok(None) => "The draft doesn't exist anymore".to_string(),
+        Err(err) => err.listing_problem().unwrap_or_else(|| {
+            error!(?err, "Database error while creating listing");
+            "Internal server error, try again later".to_string()
+        }),
+    };
+
+    // the form stays filled in, the draft has everything
+    let page = templates::pages::CreateListing { error: Some(error), ..listing_form_page(&backend, author, Some(draft)).await };
+    render_htmx_page(true, None, auth_session, Box::new(page)).into_response()
+}
+
+/// Saves the form in the background while it's being filled in.
+async fn autosave_draft(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    TypedMultipart(mut form): TypedMultipart<ListingForm>,
+) -> impl IntoResponse {
+    let author = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    let draft = form.draft.take().and_then(|draft| Uuid::parse_str(&draft).ok());
+
+    match save_listing_form(&backend, author, draft, form).await {
+        Ok(id) => templates::pages::DraftStatus { draft: Some(id), message: "Draft saved".to_string() },
+        Err(error) => templates::pages::DraftStatus { draft, message: error },
+    }
+}
+
+async fn render_drafts(
+    HxRequest(is_htmx): HxRequest,
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+) -> impl IntoResponse {
+    let author = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    let page: Box<dyn DynTemplate> = match backend.get_drafts(author).await {
+        Ok(drafts) => Box::new(templates::pages::Drafts { drafts }),
+        Err(err) => {
+            error!(?err, "Error while getting drafts");
+            Box::new(templates::pages::Error::new("Internal server error"))
+        }
+    };
+
+    render_htmx_page(is_htmx, None, auth_session, page)
+}
+
+async fn resume_draft(
+    HxRequest(is_htmx): HxRequest,
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let author = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    let page = listing_form_page(&backend, author, Some(id)).await;
+    if page.draft.is_none() {
+        let page = templates::pages::Error404Page;
+        let rendered_page = render_htmx_page(is_htmx, None, auth_session, Box::new(page));
+        return (StatusCode::NOT_FOUND, rendered_page).into_response();
+    }
+
+    render_htmx_page(is_htmx, None, auth_session, Box::new(page)).into_response()
+}
+
+async fn delete_draft(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let author = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    match backend.delete_draft(id, author).await {
+        // the row gets replaced with nothing
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while deleting draft");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Uploads the pictures of a listing form, returns the error to show otherwise.
+async fn upload_pictures(
+    backend: &Backend,
+    user: Uuid,
+    pictures: &[FieldData<axum::body::Bytes>],
+) -> Result<Vec<Uuid>, &'static str> {
+    if pictures.is_empty() {
+        return Err("You need to upload at least one image");
+    }
+
+    for picture in pictures {
         let content_type = picture.metadata.content_type
             .as_ref()
             .map(|f| f.as_ref());
         if content_type != Some("image/jpeg") && content_type != Some("image/png") {
             error!(?content_type, "Invalid content type");
-            let page = templates::pages::CreateListing::with_error("Invalid image type");
-            return render_htmx_page(true, None, auth_session, Box::new(page)).into_response();
+            return Err("Invalid image type");
         }
     }
 
-    let user_id = auth_session.user.as_ref().unwrap().claims.user_id;
-
     let mut picture_ids = Vec::new();
 
-    for picture in &body.pictures {
+    for picture in pictures {
         let upload_result = backend
-            .upload_image(user_id, picture.contents.clone())
+            .upload_image(user, picture.contents.clone())
             .await;
         match upload_result {
             Ok(uuid) => picture_ids.push(uuid),
+            Err(BackendError::QuotaExceeded(kind)) => return Err(kind.message()),
             Err(err) => {
                 error!(?err, "Error while uploading image");
-                let page = templates::pages::CreateListing::with_error("Internal server error");
-                return render_htmx_page(true, None, auth_session, Box::new(page)).into_response();
+                return Err("Internal server error");
             }
         }
     }
 
-    let thumbnail = picture_ids.first().unwrap();
+    Ok(picture_ids)
+}
 
-    let insert_listing = body.into_insert_listing(author, *thumbnail);
+#[derive(TryFromMultipart)]
+struct SuggestPlantsBody {
+    #[form_data(limit = "10MiB")]
+    pub pictures: Vec<FieldData<axum::body::Bytes>>,
+}
 
-    match backend.create_listing(insert_listing).await {
-        Ok(listing) => {
-            let id = listing.id;
+/// Uploads the pictures as soon as they're picked and starts recognising
+/// the plant on them. The suggestions are then polled from `render_plant_suggestions`.
+async fn suggest_plants(
+    auth_session: AuthSession,
+    headers: HeaderMap,
+    State(backend): State<Backend>,
+    TypedMultipart(body): TypedMultipart<SuggestPlantsBody>,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().unwrap().claims.user_id;
 
-            let human_name = convert_title_to_human_url(listing.title);
+    let uploaded_pictures = match upload_pictures(&backend, user, &body.pictures).await {
+        Ok(picture_ids) => picture_ids,
+        Err(error) => return templates::pages::PlantSuggestions::failed(error.to_string()),
+    };
 
-            Redirect::permanent(&format!("/listing/{human_name}/{id}")).into_response()
+    let request = RecognitionRequest {
+        pictures: uploaded_pictures.iter().map(|picture| (*picture, Organ::Auto)).collect(),
+        location: None,
+        language: user_language(&backend, &auth_session, &headers).await,
+    };
+
+    let mut suggestions = match backend.recognition_jobs.submit(Some(user), request) {
+        Ok(job_id) => templates::pages::PlantSuggestions::pending(job_id),
+        Err(err) => {
+            warn!(?err, "Couldn't queue plant recognition for new listing");
+            templates::pages::PlantSuggestions::failed("Plant suggestions are currently unavailable".to_string())
         }
-        Err(BackendError::ListingHasNoLocation) => {
-            let page = templates::pages::CreateListing::with_error(
-                "Your account needs to have a location set in order to create a listing"
-            );
-            render_htmx_page(true, None, auth_session, Box::new(page)).into_response()
+    };
+    suggestions.uploaded_pictures = uploaded_pictures;
+
+    suggestions
+}
+
+async fn render_plant_suggestions(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(job_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    match backend.recognition_jobs.state(job_id, Some(user)) {
+        None => templates::pages::PlantSuggestions::failed("Plant suggestions are not available anymore".to_string()),
+        Some(RecognitionJobState::Queued | RecognitionJobState::Running) => {
+            templates::pages::PlantSuggestions::pending(job_id)
         }
-        Err(err) => {
-            error!(?err, "Database error while creating listing");
-            let page = templates::pages::CreateListing::with_error("Internal server error, try again later");
-            render_htmx_page(true, None, auth_session, Box::new(page)).into_response()
+        Some(RecognitionJobState::Done { result }) => {
+            let mut suggestions = templates::pages::PlantSuggestions::done(result.plants);
+
+            // shown next to the plants, so authors know before they submit
+            for ranked in &suggestions.plants {
+                match backend.get_plant_restrictions(ranked.plant.id, Some(user)).await {
+                    Ok(restrictions) => suggestions.restrictions.extend(restrictions.into_iter().map(|restriction| (ranked.plant.id, restriction))),
+                    Err(err) => error!(?err, plant = ?ranked.plant.id, "Error while getting trade restrictions of plant"),
+                }
+            }
+
+            suggestions
+        }
+        Some(RecognitionJobState::Failed { message, .. }) => {
+            templates::pages::PlantSuggestions::failed(message)
         }
     }
 }
@@ -225,12 +964,76 @@ async fn render_create_listing(
     )
 }
 
+/// The filter form of the discover page, empty fields don't filter.
+#[derive(Deserialize, Debug, Default)]
+struct DiscoverQuery {
+    pub listing_type: Option<String>,
+    pub propagation_form: Option<String>,
+    pub min_quantity: Option<String>,
+    pub max_pot_size_cm: Option<String>,
+    pub currency: Option<String>,
+    /// In the currency, e.g. 12.50
+    pub min_price: Option<String>,
+    pub max_price: Option<String>,
+    pub sort: Option<String>,
+    /// "true" if checked
+    pub shippable: Option<String>,
+    pub pickup_within_km: Option<String>,
+}
+
+impl DiscoverQuery {
+    fn into_filter(self) -> Result<ListingFilter, &'static str> {
+        let currency = self.currency
+            .map(|currency| currency.trim().to_uppercase())
+            .filter(|currency| !currency.is_empty());
+        let parse_bound = |price: Option<String>| match (price.filter(|price| !price.trim().is_empty()), &currency) {
+            (Some(price), Some(currency)) => parse_price(&price, currency).map(Some).ok_or("Prices have to be amounts like 12.50"),
+            (Some(_), None) => Err("Price filters need a currency"),
+            (None, _) => Ok(None),
+        };
+        let (min_price, max_price) = (parse_bound(self.min_price)?, parse_bound(self.max_price)?);
+        // the currency input has a default, it only filters with a price
+        let currency = currency.filter(|_| min_price.is_some() || max_price.is_some());
+
+        Ok(ListingFilter {
+            listing_type: parse_choice(self.listing_type)?,
+            propagation_form: parse_choice(self.propagation_form)?,
+            min_quantity: parse_number(self.min_quantity, "The quantity has to be a number")?,
+            max_pot_size_cm: parse_number(self.max_pot_size_cm, "The pot size has to be a number")?,
+            currency,
+            min_price,
+            max_price,
+            sort: parse_choice(self.sort)?.unwrap_or_default(),
+            shippable: self.shippable.is_some_and(|shippable| shippable == "true"),
+            pickup_within_km: parse_number(self.pickup_within_km, "The pickup distance has to be a number")?,
+            ..Default::default()
+        })
+    }
+}
+
 async fn render_discover(
     State(backend): State<Backend>,
     auth_session: AuthSession,
     HxRequest(is_htmx): HxRequest,
+    Query(query): Query<DiscoverQuery>,
 ) -> impl IntoResponse {
-    let listings = match backend.get_all_listings().await {
+    let mut filter = match query.into_filter() {
+        Ok(filter) => filter,
+        Err(error) => {
+            let page = templates::pages::Error::new(error);
+            let rendered_page = render_htmx_page(is_htmx, Some(PageSelection::Discover), auth_session, Box::new(page));
+            return (StatusCode::BAD_REQUEST, rendered_page).into_response();
+        }
+    };
+
+    filter.viewer = auth_session.user.as_ref().map(|user| user.claims.user_id);
+
+    let listings = match backend.search_listings(&filter).await {
+        Err(BackendError::InvalidListingFilter(_)) if filter.pickup_within_km.is_some() => {
+            let page = templates::pages::Error::new("Log in and set your location to find listings nearby");
+            let rendered_page = render_htmx_page(is_htmx, Some(PageSelection::Discover), auth_session, Box::new(page));
+            return (StatusCode::BAD_REQUEST, rendered_page).into_response();
+        }
         Err(err) => {
             error!(?err, "Discover page failed");
             let page = templates::pages::Error::new("Internal server error");
@@ -245,7 +1048,7 @@ async fn render_discover(
         Ok(listings) => listings,
     };
 
-    let page = templates::pages::Discover { listings };
+    let page = templates::pages::Discover { listings, filter };
     render_htmx_page(
         is_htmx,
         Some(PageSelection::Discover),
@@ -255,6 +1058,199 @@ async fn render_discover(
     .into_response()
 }
 
+async fn render_suspected_duplicates(
+    auth_session: AuthSession,
+    HxRequest(is_htmx): HxRequest,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        let page = templates::pages::Error::new("403 Only moderators can see this page");
+        let rendered_page = render_htmx_page(is_htmx, None, auth_session, Box::new(page));
+        return (StatusCode::FORBIDDEN, rendered_page).into_response();
+    }
+
+    let page: Box<dyn DynTemplate> = match backend.get_suspected_duplicates().await {
+        Err(err) => {
+            error!(?err, "Error while getting suspected duplicates");
+            Box::new(templates::pages::Error::new("Internal server error"))
+        }
+        Ok(duplicates) => Box::new(templates::pages::SuspectedDuplicates { duplicates }),
+    };
+
+    render_htmx_page(is_htmx, None, auth_session, page).into_response()
+}
+
+async fn dismiss_suspected_duplicate(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.dismiss_suspected_duplicate(id).await {
+        // the row gets replaced with nothing
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while dismissing suspected duplicate");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn render_trade_restrictions(
+    auth_session: AuthSession,
+    HxRequest(is_htmx): HxRequest,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        let page = templates::pages::Error::new("403 Only moderators can see this page");
+        let rendered_page = render_htmx_page(is_htmx, None, auth_session, Box::new(page));
+        return (StatusCode::FORBIDDEN, rendered_page).into_response();
+    }
+
+    let page = trade_restrictions_page(&backend, None).await;
+
+    render_htmx_page(is_htmx, None, auth_session, page).into_response()
+}
+
+async fn trade_restrictions_page(backend: &Backend, error: Option<String>) -> Box<dyn DynTemplate> {
+    match backend.get_trade_restrictions().await {
+        Ok(restrictions) => Box::new(templates::pages::TradeRestrictions { restrictions, error }),
+        Err(err) => {
+            error!(?err, "Error while getting trade restrictions");
+            Box::new(templates::pages::Error::new("Internal server error"))
+        }
+    }
+}
+
+/// Either the species' POWO id or a genus or family name. Without the
+/// region name and bounds the restriction applies everywhere.
+#[derive(TryFromMultipart)]
+struct TradeRestrictionForm {
+    pub powo_id: String,
+    pub taxon: String,
+    pub level: String,
+    pub reason: String,
+    pub region_name: String,
+    pub west: String,
+    pub south: String,
+    pub east: String,
+    pub north: String,
+}
+
+impl TradeRestrictionForm {
+    /// Bounds of the region, None if they're all empty.
+    fn region_bounds(&self) -> Result<Option<RegionBounds>, &'static str> {
+        let fields = [&self.west, &self.south, &self.east, &self.north];
+
+        if fields.iter().all(|field| field.trim().is_empty()) {
+            return Ok(None);
+        }
+
+        let [west, south, east, north] = fields.map(|field| field.trim().parse::<f64>());
+        match (west, south, east, north) {
+            (Ok(west), Ok(south), Ok(east), Ok(north)) => Ok(Some(RegionBounds { west, south, east, north })),
+            _ => Err("The region bounds have to be numbers"),
+        }
+    }
+}
+
+async fn add_trade_restriction(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    TypedMultipart(form): TypedMultipart<TradeRestrictionForm>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let restriction = match trade_restriction_from_form(&backend, form).await {
+        Ok(restriction) => restriction,
+        Err(message) => {
+            let page = trade_restrictions_page(&backend, Some(message)).await;
+            return render_htmx_page(true, None, auth_session, page).into_response();
+        }
+    };
+
+    let error = match backend.add_trade_restriction(restriction).await {
+        Ok(_) => None,
+        Err(BackendError::InvalidTradeRestriction(message)) => Some(message),
+        Err(err) => {
+            error!(?err, "Error while adding trade restriction");
+            Some("Internal server error".to_string())
+        }
+    };
+
+    let page = trade_restrictions_page(&backend, error).await;
+    render_htmx_page(true, None, auth_session, page).into_response()
+}
+
+async fn trade_restriction_from_form(backend: &Backend, form: TradeRestrictionForm) -> Result<InsertTradeRestriction, String> {
+    let level = match form.level.as_str() {
+        "warn" => RestrictionLevel::Warn,
+        "block" => RestrictionLevel::Block,
+        _ => return Err("Invalid choice".to_string()),
+    };
+
+    let region = match form.region_bounds()? {
+        Some(bounds) => Some(bounds.to_polygon().ok_or("The region bounds aren't on the globe")?),
+        None => None,
+    };
+
+    let taxon_name = form.taxon.trim();
+    let taxon_id = if taxon_name.is_empty() {
+        None
+    } else {
+        let taxa = backend.search_taxa(taxon_name).await.map_err(|err| {
+            error!(?err, taxon_name, "Error while searching taxa");
+            "Internal server error".to_string()
+        })?;
+
+        // a family and a genus can share a name, the genus is more specific
+        let taxon = taxa.into_iter()
+            .filter(|taxon| taxon.name.eq_ignore_ascii_case(taxon_name))
+            .max_by_key(|taxon| taxon.rank == TaxonRank::Genus)
+            .ok_or_else(|| format!("There's no genus or family called {taxon_name}"))?;
+
+        Some(taxon.id)
+    };
+
+    Ok(InsertTradeRestriction {
+        powo_id: Some(form.powo_id),
+        taxon_id,
+        level,
+        reason: form.reason,
+        region_name: Some(form.region_name),
+        region,
+    })
+}
+
+async fn remove_trade_restriction(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.remove_trade_restriction(id).await {
+        // the row gets replaced with nothing
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while removing trade restriction");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 async fn render_homepage(
     HxRequest(is_htmx): HxRequest,
     auth_session: AuthSession,
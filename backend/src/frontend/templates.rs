@@ -2,7 +2,7 @@ use askama::DynTemplate;
 use askama_axum::Template;
 use chrono::{Local, NaiveDateTime};
 
-use crate::auth::AuthSession;
+use crate::{auth::AuthSession, backend::image_analysis::blurhash_data_uri};
 
 use super::PageSelection;
 
@@ -38,10 +38,11 @@ pub struct LoginButton {
 
 pub mod pages {
     use askama_axum::Template;
+    use uuid::Uuid;
 
-    use crate::frontend::components;
-    use super::generate_insertion_date;
-    pub use crate::models::Listing;
+    use crate::{backend::{recognition::RankedPlant, DraftWithPictures, Identification, ListingFilter, ListingRecommendations, ListingSort, SimilarPlant, TaxonDetails}, frontend::components, models::{format_amount, DraftFields, LightRequirement, ListingDraft, ListingType, Plant, PlantCare, PlantEdit, PlantSeason, PriceMode, PropagationForm, PropagationMethod, RestrictionLevel, Taxon, TradeRestriction, WateringFrequency}};
+    use super::{care_fields, generate_insertion_date, handover_options, listing_offer, listing_price, listing_url, placeholder_style, score_percent};
+    pub use crate::models::{IdentificationState, ImagePlaceholder, Listing, ListingWithPlaceholder, SuspectedDuplicate};
 
     #[derive(Template)]
     #[template(path = "pages/about.html")]
@@ -54,29 +55,390 @@ pub mod pages {
     #[derive(Template)]
     #[template(path = "pages/discover.html")]
     pub struct Discover {
-        pub listings: Vec<Listing>,
+        pub listings: Vec<ListingWithPlaceholder>,
+        pub filter: ListingFilter,
+    }
+
+    impl Discover {
+        fn listing_type_choices(&self) -> Vec<Choice> {
+            choices(&ListingType::ALL, &self.filter.listing_type, ListingType::as_str, ListingType::label)
+        }
+
+        fn propagation_form_choices(&self) -> Vec<Choice> {
+            choices(PropagationForm::ALL, &self.filter.propagation_form, PropagationForm::as_str, PropagationForm::label)
+        }
+
+        fn min_quantity(&self) -> String {
+            self.filter.min_quantity.map(|quantity| quantity.to_string()).unwrap_or_default()
+        }
+
+        fn max_pot_size_cm(&self) -> String {
+            self.filter.max_pot_size_cm.map(|size| size.to_string()).unwrap_or_default()
+        }
+
+        fn currency(&self) -> &str {
+            self.filter.currency.as_deref().unwrap_or("EUR")
+        }
+
+        fn pickup_within_km(&self) -> String {
+            self.filter.pickup_within_km.map(|distance| distance.to_string()).unwrap_or_default()
+        }
+
+        fn min_price(&self) -> String {
+            self.filter.min_price.map(|price| format_amount(price, self.currency())).unwrap_or_default()
+        }
+
+        fn max_price(&self) -> String {
+            self.filter.max_price.map(|price| format_amount(price, self.currency())).unwrap_or_default()
+        }
+
+        /// Newest first is the default, so it's the empty choice.
+        fn sort_choices(&self) -> Vec<Choice> {
+            ListingSort::ALL.iter()
+                .filter(|sort| **sort != ListingSort::Newest)
+                .map(|sort| (sort.as_str(), sort.label(), *sort == self.filter.sort))
+                .collect()
+        }
     }
 
     #[derive(Template)]
     #[template(path = "pages/show_listing.html")]
     pub struct ShowListing {
-        pub listing: Listing
+        pub listing: Listing,
+        pub thumbnail_placeholder: Option<ImagePlaceholder>,
+        pub identification: Option<ListingIdentification>,
+        pub recommendations: ListingRecommendations,
+        /// Restrictions that only warn about trading the plant, for the author.
+        pub restriction_warnings: Vec<TradeRestriction>,
+    }
+
+    #[derive(Template)]
+    #[template(path = "pages/listing_identification.html")]
+    pub struct ListingIdentification {
+        pub listing: Listing,
+        pub identification: Identification,
+        /// None if the user isn't logged in, they can't vote then.
+        pub viewer: Option<Uuid>,
+    }
+
+    impl ListingIdentification {
+        /// Plants that can be voted for, with their votes. The identified
+        /// plant is always one of them.
+        fn candidates(&self) -> Vec<(&Plant, usize)> {
+            let mut candidates = self.identification.votes.iter()
+                .map(|plant_votes| (&plant_votes.plant, plant_votes.votes))
+                .collect::<Vec<_>>();
+
+            if let Some(plant) = &self.identification.plant {
+                if !candidates.iter().any(|(candidate, _)| candidate.id == plant.id) {
+                    candidates.insert(0, (plant, 0));
+                }
+            }
+
+            candidates
+        }
+
+        fn identification_url(&self) -> String {
+            format!("{}/identification", listing_url(&self.listing))
+        }
+
+        fn is_own_vote(&self, plant: &Plant) -> bool {
+            self.identification.own_vote == Some(plant.id)
+        }
+
+        fn is_author(&self) -> bool {
+            self.viewer == Some(self.listing.author)
+        }
+
+        fn state_color(state: &IdentificationState) -> &'static str {
+            match state {
+                IdentificationState::CommunityVerified => "text-green-400",
+                IdentificationState::Disputed => "text-yellow-300",
+                IdentificationState::AiSuggested | IdentificationState::AuthorConfirmed => "text-gray-300",
+            }
+        }
+    }
+
+    #[derive(Template)]
+    #[template(path = "pages/show_plant.html")]
+    pub struct ShowPlant {
+        pub plant: Plant,
+        /// Names in the other languages.
+        pub other_names: Vec<String>,
+        pub listings: Vec<ListingWithPlaceholder>,
+        pub similar: Vec<SimilarPlant>,
+        /// Genus and family.
+        pub taxa: Vec<Taxon>,
+        /// Shows the form for editing the care information.
+        pub can_edit: bool,
+    }
+
+    #[derive(Template)]
+    #[template(path = "pages/show_taxon.html")]
+    pub struct ShowTaxon {
+        pub details: TaxonDetails,
+        pub listings: Vec<ListingWithPlaceholder>,
+    }
+
+    impl ShowPlant {
+        fn watering_choices(&self) -> Vec<Choice> {
+            choices(WateringFrequency::ALL, &self.plant.watering, WateringFrequency::as_str, WateringFrequency::label)
+        }
+
+        fn light_choices(&self) -> Vec<Choice> {
+            choices(LightRequirement::ALL, &self.plant.light, LightRequirement::as_str, LightRequirement::label)
+        }
+
+        fn season_choices(&self) -> Vec<Choice> {
+            choices(PlantSeason::ALL, &self.plant.season, PlantSeason::as_str, PlantSeason::label)
+        }
+
+        fn toxicity_choices(&self) -> Vec<Choice> {
+            vec![
+                ("true", "Yes", self.plant.toxic_to_pets == Some(true)),
+                ("false", "No", self.plant.toxic_to_pets == Some(false)),
+            ]
+        }
+
+        fn propagation_choices(&self) -> Vec<Choice> {
+            PropagationMethod::ALL.iter()
+                .map(|method| (method.as_str(), method.label(), self.plant.propagation_methods.contains(method)))
+                .collect()
+        }
+    }
+
+    /// Value, label and whether it's selected
+    type Choice = (&'static str, &'static str, bool);
+
+    fn choices<T: PartialEq>(
+        all: &[T],
+        current: &Option<T>,
+        value: fn(&T) -> &'static str,
+        label: fn(&T) -> &'static str,
+    ) -> Vec<Choice> {
+        all.iter()
+            .map(|choice| (value(choice), label(choice), current.as_ref() == Some(choice)))
+            .collect()
+    }
+
+    #[derive(Template)]
+    #[template(source = "<p class=\"py-2 {% if error %}text-red-400{% else %}text-gray-300{% endif %}\">{{ message }}</p>", ext = "html")]
+    pub struct PlantEditResult<'a> {
+        pub message: &'a str,
+        pub error: bool,
+    }
+
+    #[derive(Template)]
+    #[template(path = "pages/plant_edits.html")]
+    pub struct PlantEdits {
+        pub edits: Vec<(PlantEdit, Plant, PlantCare)>,
     }
 
     #[derive(Template)]
     #[template(path = "pages/create_listing.html")]
-    pub struct CreateListing<'a> {
-        pub error: Option<&'a str>,
+    pub struct CreateListing {
+        /// The form gets filled in from it.
+        pub draft: Option<DraftWithPictures>,
+        pub error: Option<String>,
+        pub message: Option<String>,
     }
 
-    impl<'a> CreateListing<'a> {
+    impl CreateListing {
         pub fn new() -> Self {
-            Self { error: None }
+            Self { draft: None, error: None, message: None }
+        }
+
+        pub fn with_error(error: impl Into<String>) -> Self {
+            Self { error: Some(error.into()), ..Self::new() }
+        }
+
+        fn fields(&self) -> Option<&DraftFields> {
+            self.draft.as_ref().map(|draft| &draft.draft.fields)
+        }
+
+        fn draft_status(&self) -> DraftStatus {
+            DraftStatus { draft: self.draft.as_ref().map(|draft| draft.draft.id), message: String::new() }
+        }
+
+        fn pictures(&self) -> &[Uuid] {
+            self.draft.as_ref().map(|draft| draft.pictures.as_slice()).unwrap_or_default()
+        }
+
+        fn identified_plant(&self) -> Option<Uuid> {
+            self.fields().and_then(|fields| fields.identified_plant)
+        }
+
+        fn title(&self) -> &str {
+            self.fields().and_then(|fields| fields.title.as_deref()).unwrap_or_default()
+        }
+
+        fn description(&self) -> &str {
+            self.fields().and_then(|fields| fields.description.as_deref()).unwrap_or_default()
+        }
+
+        /// Selling is the default.
+        fn listing_type_choices(&self) -> Vec<(&'static str, bool)> {
+            let current = self.fields().and_then(|fields| fields.listing_type.clone()).unwrap_or(ListingType::Selling);
+
+            ListingType::ALL.iter()
+                .map(|listing_type| (listing_type.as_str(), *listing_type == current))
+                .collect()
+        }
+
+        fn tradeable(&self) -> bool {
+            self.fields().and_then(|fields| fields.tradeable).unwrap_or(false)
+        }
+
+        fn price_negotiable(&self) -> bool {
+            self.fields().and_then(|fields| fields.price_negotiable).unwrap_or(false)
+        }
+
+        /// Possible unless the author says otherwise.
+        fn local_pickup(&self) -> bool {
+            self.fields().and_then(|fields| fields.local_pickup).unwrap_or(true)
+        }
+
+        fn meet_halfway(&self) -> bool {
+            self.fields().and_then(|fields| fields.meet_halfway).unwrap_or(false)
         }
 
-        pub fn with_error(error: &'a str) -> Self {
-            Self { error: Some(error) }
+        fn shipping(&self) -> bool {
+            self.fields().and_then(|fields| fields.shipping).unwrap_or(false)
         }
+
+        fn price_mode_choices(&self) -> Vec<Choice> {
+            choices(PriceMode::ALL, &self.fields().and_then(|fields| fields.price_mode), PriceMode::as_str, PriceMode::label)
+        }
+
+        /// Mature plant is the default, so it's the empty choice.
+        fn propagation_form_choices(&self) -> Vec<Choice> {
+            let current = self.fields().and_then(|fields| fields.propagation_form);
+
+            PropagationForm::ALL.iter()
+                .filter(|form| **form != PropagationForm::MaturePlant)
+                .map(|form| (form.as_str(), form.label(), Some(*form) == current))
+                .collect()
+        }
+
+        /// The price and the shipping cost share it.
+        fn currency(&self) -> &str {
+            self.fields()
+                .and_then(|fields| fields.currency.as_deref().or(fields.shipping_currency.as_deref()))
+                .unwrap_or("EUR")
+        }
+
+        fn price(&self) -> String {
+            self.fields().and_then(|fields| fields.price_minor)
+                .map(|price| format_amount(price, self.currency()))
+                .unwrap_or_default()
+        }
+
+        fn shipping_cost(&self) -> String {
+            self.fields().and_then(|fields| fields.shipping_cost_minor)
+                .map(|cost| format_amount(cost, self.currency()))
+                .unwrap_or_default()
+        }
+
+        fn quantity(&self) -> String {
+            self.fields().and_then(|fields| fields.quantity).map(|quantity| quantity.to_string()).unwrap_or_default()
+        }
+
+        fn pot_size_cm(&self) -> String {
+            self.fields().and_then(|fields| fields.pot_size_cm).map(|size| size.to_string()).unwrap_or_default()
+        }
+
+        fn max_pickup_distance_km(&self) -> String {
+            self.fields().and_then(|fields| fields.max_pickup_distance_km).map(|distance| distance.to_string()).unwrap_or_default()
+        }
+
+        /// In the format of `datetime-local` inputs.
+        fn publish_at(&self) -> String {
+            self.draft.as_ref().and_then(|draft| draft.draft.publish_at)
+                .map(|publish_at| publish_at.format("%Y-%m-%dT%H:%M").to_string())
+                .unwrap_or_default()
+        }
+    }
+
+    /// Saves the create listing form whenever it changes, and remembers
+    /// the draft it was saved to.
+    #[derive(Template)]
+    #[template(path = "pages/draft_status.html")]
+    pub struct DraftStatus {
+        pub draft: Option<Uuid>,
+        pub message: String,
+    }
+
+    #[derive(Template)]
+    #[template(path = "pages/drafts.html")]
+    pub struct Drafts {
+        pub drafts: Vec<ListingDraft>,
+    }
+
+    /// Replaces itself with the next state while the recognition is running.
+    #[derive(Template, Default)]
+    #[template(path = "pages/plant_suggestions.html")]
+    pub struct PlantSuggestions {
+        /// Pictures that were just uploaded, they replace the file input.
+        pub uploaded_pictures: Vec<Uuid>,
+        pub pending_job: Option<Uuid>,
+        pub plants: Vec<RankedPlant>,
+        /// Trade restrictions of the suggested plants, by plant id.
+        pub restrictions: Vec<(Uuid, TradeRestriction)>,
+        pub error: Option<String>,
+    }
+
+    impl PlantSuggestions {
+        /// More candidates than this are hardly ever right.
+        const MAX_SUGGESTIONS: usize = 3;
+
+        pub fn pending(job_id: Uuid) -> Self {
+            Self { pending_job: Some(job_id), ..Default::default() }
+        }
+
+        pub fn done(mut plants: Vec<RankedPlant>) -> Self {
+            plants.truncate(Self::MAX_SUGGESTIONS);
+            Self { plants, ..Default::default() }
+        }
+
+        pub fn failed(error: String) -> Self {
+            Self { error: Some(error), ..Default::default() }
+        }
+
+        fn restrictions_of(&self, plant: &Plant) -> Vec<&TradeRestriction> {
+            self.restrictions.iter()
+                .filter(|(plant_id, _)| *plant_id == plant.id)
+                .map(|(_, restriction)| restriction)
+                .collect()
+        }
+
+        fn restriction_color(restriction: &TradeRestriction) -> &'static str {
+            match restriction.level {
+                RestrictionLevel::Block => "text-red-400",
+                RestrictionLevel::Warn => "text-yellow-300",
+            }
+        }
+    }
+
+    #[derive(Template)]
+    #[template(path = "pages/trade_restrictions.html")]
+    pub struct TradeRestrictions {
+        pub restrictions: Vec<(TradeRestriction, Option<Taxon>)>,
+        /// Why the last restriction couldn't be added.
+        pub error: Option<String>,
+    }
+
+    impl TradeRestrictions {
+        fn level_choices(&self) -> Vec<Choice> {
+            RestrictionLevel::ALL.iter()
+                .map(|level| (level.as_str(), level.label(), *level == RestrictionLevel::Warn))
+                .collect()
+        }
+    }
+
+    #[derive(Template)]
+    #[template(path = "pages/suspected_duplicates.html")]
+    pub struct SuspectedDuplicates {
+        pub duplicates: Vec<(SuspectedDuplicate, Listing)>,
     }
 
     #[derive(Template)]
@@ -123,6 +485,104 @@ fn generate_insertion_date(insertion_date: &NaiveDateTime) -> (String, String) {
     (human_duration, insertion_date.to_string())
 }
 
+fn listing_url(listing: &crate::models::Listing) -> String {
+    let human_name = super::convert_title_to_human_url(listing.title.clone());
+    format!("/listing/{human_name}/{}", listing.id)
+}
+
+/// What exactly is offered, e.g. "3 × Cutting" or "Mature plant, 12 cm pot".
+fn listing_offer(listing: &crate::models::Listing) -> String {
+    let mut offer = listing.propagation_form.label().to_string();
+
+    if listing.quantity > 1 {
+        offer = format!("{} × {offer}", listing.quantity);
+    }
+    if let Some(pot_size_cm) = listing.pot_size_cm {
+        offer = format!("{offer}, {pot_size_cm} cm pot");
+    }
+
+    offer
+}
+
+/// e.g. "12.50 EUR, negotiable", None if the listing doesn't say.
+fn listing_price(listing: &crate::models::Listing) -> Option<String> {
+    use crate::models::{format_price, PriceMode};
+
+    match (listing.price_mode?, listing.price_minor, &listing.currency) {
+        (PriceMode::Fixed, Some(price_minor), Some(currency)) if listing.price_negotiable =>
+            Some(format!("{}, negotiable", format_price(price_minor, currency))),
+        (PriceMode::Fixed, Some(price_minor), Some(currency)) => Some(format_price(price_minor, currency)),
+        (PriceMode::Fixed, _, _) => None,
+        (mode, _, _) => Some(mode.label().to_string()),
+    }
+}
+
+/// e.g. "Local pickup up to 20 km away" and "Shipping for 4.90 EUR"
+fn handover_options(listing: &crate::models::Listing) -> Vec<String> {
+    let distance = listing.max_pickup_distance_km
+        .map(|distance| format!(" up to {distance} km away"))
+        .unwrap_or_default();
+
+    let mut options = Vec::new();
+
+    if listing.local_pickup {
+        options.push(format!("Local pickup{distance}"));
+    }
+    if listing.meet_halfway {
+        options.push(format!("Meeting halfway{distance}"));
+    }
+    if listing.shipping {
+        match (listing.shipping_cost_minor, &listing.shipping_currency) {
+            (Some(cost), Some(currency)) => options.push(format!("Shipping for {}", crate::models::format_price(cost, currency))),
+            _ => options.push("Shipping".to_string()),
+        }
+    }
+
+    options
+}
+
+/// Inline style showing the placeholder until the image covers it.
+fn placeholder_style(placeholder: &Option<crate::models::ImagePlaceholder>) -> String {
+    let color = placeholder.as_ref().and_then(|placeholder| placeholder.dominant_color.as_deref()).unwrap_or("#808080");
+
+    match placeholder.as_ref().and_then(|placeholder| placeholder.blurhash.as_deref()).and_then(blurhash_data_uri) {
+        Some(data_uri) => format!("background-color: {color}; background-image: url({data_uri}); background-size: cover;"),
+        None => format!("background-color: {color};"),
+    }
+}
+
+/// Label and value of every known care field.
+fn care_fields(care: &crate::models::PlantCare) -> Vec<(&'static str, String)> {
+    let mut fields = Vec::new();
+
+    if let Some(watering) = care.watering {
+        fields.push(("Watering", watering.label().to_string()));
+    }
+    if let Some(light) = care.light {
+        fields.push(("Light", light.label().to_string()));
+    }
+    if let Some(season) = care.season {
+        fields.push(("Season", season.label().to_string()));
+    }
+    if let Some(zone) = care.hardiness_zone {
+        fields.push(("Hardiness", format!("USDA zone {zone}")));
+    }
+    if let Some(toxic) = care.toxic_to_pets {
+        fields.push(("Toxic to pets", if toxic { "Yes" } else { "No" }.to_string()));
+    }
+    if let Some(methods) = &care.propagation_methods {
+        let methods = methods.iter().map(|method| method.label()).collect::<Vec<_>>();
+        fields.push(("Propagation", if methods.is_empty() { "None".to_string() } else { methods.join(", ") }));
+    }
+
+    fields
+}
+
+/// Recognition scores are between 0 and 1.
+fn score_percent(score: &f32) -> u32 {
+    (score * 100.0).round() as u32
+}
+
 fn is_current_selection(selection: &Option<PageSelection>, current_selection: &Option<PageSelection>) -> bool {
     selection.is_some_and(|s| &Some(s) == current_selection)
 }
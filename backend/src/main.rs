@@ -1,17 +1,18 @@
-use std::sync::Arc;
+use std::{path::{Path, PathBuf}, process::ExitCode, sync::Arc};
 
 use auth::initialize_auth;
 use axum::{extract::FromRef, response::Redirect, routing::get, Router};
 use axum_login::{tower_sessions::{CachingSessionStore, Expiry, SessionManagerLayer}, AuthManagerLayerBuilder};
-use backend::Backend;
+use backend::{catalogue_import::CatalogueFormat, Backend};
+use clap::{Parser, Subcommand};
 use config::AppConfig;
 use tower_http::{services::ServeDir, ServiceBuilderExt};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_sessions_moka_store::MokaStore;
 use tower_sessions_redis_store::{fred::{prelude::*, types::RedisConfig}, RedisStore};
-use tracing::{info, warn};
-use tracing_subscriber::EnvFilter;
+use tracing::{error, info, warn};
+use tracing_subscriber::{filter::LevelFilter, EnvFilter};
 
 mod frontend;
 mod backend;
@@ -29,17 +30,72 @@ struct AppState {
 
 const LOGIN_URL: &str = "/auth/login";
 
+/// Runs the web app, unless a command is given.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Imports species and taxonomy from downloaded reference data into the
+    /// plant catalogue. Can be run again with newer data.
+    ImportCatalogue {
+        #[arg(value_enum)]
+        format: CatalogueFormat,
+        /// The downloaded archive, or the directory it was extracted to
+        path: PathBuf,
+    },
+}
+
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     dotenvy::from_filename(".env.local").ok();
     dotenvy::dotenv().ok();
 
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_env("PLANTS_LOG"))
-        .init();
+    let cli = Cli::parse();
+
+    match cli.command {
+        None => {
+            tracing_subscriber::fmt()
+                .with_env_filter(EnvFilter::from_env("PLANTS_LOG"))
+                .init();
+
+            serve(AppConfig::new()).await;
+            ExitCode::SUCCESS
+        }
+        Some(Command::ImportCatalogue { format, path }) => {
+            // progress is logged, so it has to show up by default
+            tracing_subscriber::fmt()
+                .with_env_filter(EnvFilter::builder()
+                    .with_default_directive(LevelFilter::INFO.into())
+                    .with_env_var("PLANTS_LOG")
+                    .from_env_lossy())
+                .init();
+
+            import_catalogue(AppConfig::new(), format, &path).await
+        }
+    }
+}
 
-    let config = AppConfig::new();
+async fn import_catalogue(config: AppConfig, format: CatalogueFormat, path: &Path) -> ExitCode {
+    let backend = Backend::new(&config).await;
+
+    match backend.import_catalogue(format, path).await {
+        Ok(stats) => {
+            info!(stats.imported, stats.skipped, ?format, "Catalogue import finished");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            error!(%err, ?format, ?path, "Catalogue import failed");
+            ExitCode::FAILURE
+        }
+    }
+}
 
+async fn serve(config: AppConfig) {
     let backend = Backend::new(&config).await;
 
     let auth_state = initialize_auth(&config, backend.clone()).await;
@@ -62,6 +118,13 @@ async fn main() {
 
     let auth_layer = AuthManagerLayerBuilder::new(auth_state, session_layer).build();
 
+    tokio::spawn(expire_direct_uploads(backend.clone()));
+    tokio::spawn(publish_scheduled_drafts(backend.clone()));
+
+    for _ in 0..config.recognition_workers() {
+        tokio::spawn(backend::recognition_jobs::run_worker(backend.clone()));
+    }
+
     let global_state = AppState { backend, config: Arc::new(config) };
 
     let app = Router::new()
@@ -97,3 +160,32 @@ async fn main() {
 
     axum::serve(listener, app).await.unwrap();
 }
+
+/// Periodically deletes direct uploads that were never confirmed.
+async fn expire_direct_uploads(backend: Backend) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(5 * 60));
+
+    loop {
+        interval.tick().await;
+
+        match backend.expire_direct_uploads().await {
+            Ok(0) => {}
+            Ok(count) => info!(count, "Deleted expired direct uploads"),
+            Err(err) => error!(?err, "Couldn't delete expired direct uploads"),
+        }
+    }
+}
+
+async fn publish_scheduled_drafts(backend: Backend) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        match backend.publish_scheduled_drafts().await {
+            Ok(0) => {}
+            Ok(count) => info!(count, "Published scheduled drafts"),
+            Err(err) => error!(?err, "Couldn't publish scheduled drafts"),
+        }
+    }
+}
@@ -2,15 +2,57 @@ use core::str;
 use std::io::Write;
 use axum_typed_multipart::TryFromField;
 use diesel::{deserialize::{self, FromSql, FromSqlRow}, expression::AsExpression, pg::{Pg, PgValue}, prelude::*, serialize::{self, IsNull, Output, ToSql}};
-use postgis_diesel::types::Point;
+use postgis_diesel::types::{Point, Polygon};
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, TryFromField, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, TryFromField, Clone)]
 #[diesel(sql_type = crate::schema::sql_types::ListingType)]
 pub enum ListingType {
+    #[default]
     Selling,
     Buying,
+    /// Offered for free
+    Giveaway,
+    /// Offered only in exchange for other plants
+    Swap,
+}
+
+impl ListingType {
+    pub const ALL: [ListingType; 4] = [ListingType::Selling, ListingType::Buying, ListingType::Giveaway, ListingType::Swap];
+
+    /// Types of listings where the author has something to give.
+    pub const OFFERS: [ListingType; 3] = [ListingType::Selling, ListingType::Giveaway, ListingType::Swap];
+
+    /// As serialized, which is also the value in forms.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListingType::Selling => "Selling",
+            ListingType::Buying => "Buying",
+            ListingType::Giveaway => "Giveaway",
+            ListingType::Swap => "Swap",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ListingType::Selling => "For sale",
+            ListingType::Buying => "Wanted",
+            ListingType::Giveaway => "Free to give away",
+            ListingType::Swap => "Swap only",
+        }
+    }
+
+    /// Whether listings of this type are tradeable regardless of what the
+    /// author chose. Nothing is expected in return for giveaways, and swaps
+    /// only accept trades, so only sellers and buyers get to choose.
+    pub fn implied_tradeable(&self) -> Option<bool> {
+        match self {
+            ListingType::Selling | ListingType::Buying => None,
+            ListingType::Giveaway => Some(false),
+            ListingType::Swap => Some(true),
+        }
+    }
 }
 
 impl ToSql<crate::schema::sql_types::ListingType, Pg> for ListingType {
@@ -18,6 +60,8 @@ impl ToSql<crate::schema::sql_types::ListingType, Pg> for ListingType {
         match *self {
             ListingType::Selling => out.write_all(b"selling")?,
             ListingType::Buying => out.write_all(b"buying")?,
+            ListingType::Giveaway => out.write_all(b"giveaway")?,
+            ListingType::Swap => out.write_all(b"swap")?,
         }
         Ok(IsNull::No)
     }
@@ -32,12 +76,175 @@ impl FromSql<crate::schema::sql_types::ListingType, Pg> for ListingType {
             Ok(ListingType::Buying)
         } else if string.eq_ignore_ascii_case("selling") {
             Ok(ListingType::Selling)
+        } else if string.eq_ignore_ascii_case("giveaway") {
+            Ok(ListingType::Giveaway)
+        } else if string.eq_ignore_ascii_case("swap") {
+            Ok(ListingType::Swap)
         } else {
             Err("Unrecognized enum variant".into())
         }
     }
 }
 
+/// What exactly is offered or wanted.
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, TryFromField, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::PropagationForm)]
+#[serde(rename_all = "snake_case")]
+#[try_from_field(rename_all = "snake_case")]
+pub enum PropagationForm {
+    /// Rooted or unrooted
+    Cutting,
+    Seedling,
+    Seeds,
+    MaturePlant,
+    Offshoot,
+}
+
+impl PropagationForm {
+    pub const ALL: &'static [PropagationForm] = &[
+        PropagationForm::Cutting,
+        PropagationForm::Seedling,
+        PropagationForm::Seeds,
+        PropagationForm::MaturePlant,
+        PropagationForm::Offshoot,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PropagationForm::Cutting => "cutting",
+            PropagationForm::Seedling => "seedling",
+            PropagationForm::Seeds => "seeds",
+            PropagationForm::MaturePlant => "mature_plant",
+            PropagationForm::Offshoot => "offshoot",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PropagationForm::Cutting => "Cutting",
+            PropagationForm::Seedling => "Seedling",
+            PropagationForm::Seeds => "Seeds",
+            PropagationForm::MaturePlant => "Mature plant",
+            PropagationForm::Offshoot => "Offshoot",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::PropagationForm, Pg> for PropagationForm {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::PropagationForm, Pg> for PropagationForm {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"cutting" => Ok(PropagationForm::Cutting),
+            b"seedling" => Ok(PropagationForm::Seedling),
+            b"seeds" => Ok(PropagationForm::Seeds),
+            b"mature_plant" => Ok(PropagationForm::MaturePlant),
+            b"offshoot" => Ok(PropagationForm::Offshoot),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+/// How the price of a listing is meant, listings without a price mode
+/// don't say anything about the price.
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, TryFromField, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::PriceMode)]
+#[serde(rename_all = "snake_case")]
+#[try_from_field(rename_all = "snake_case")]
+pub enum PriceMode {
+    /// The only mode with a price and currency
+    Fixed,
+    Free,
+    MakeAnOffer,
+}
+
+impl PriceMode {
+    pub const ALL: &'static [PriceMode] = &[PriceMode::Fixed, PriceMode::Free, PriceMode::MakeAnOffer];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceMode::Fixed => "fixed",
+            PriceMode::Free => "free",
+            PriceMode::MakeAnOffer => "make_an_offer",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PriceMode::Fixed => "Fixed price",
+            PriceMode::Free => "Free",
+            PriceMode::MakeAnOffer => "Make an offer",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::PriceMode, Pg> for PriceMode {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::PriceMode, Pg> for PriceMode {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"fixed" => Ok(PriceMode::Fixed),
+            b"free" => Ok(PriceMode::Free),
+            b"make_an_offer" => Ok(PriceMode::MakeAnOffer),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+/// Digits after the decimal point of an ISO 4217 currency, prices are
+/// stored as integers in the smallest unit.
+pub fn currency_exponent(currency: &str) -> u32 {
+    match currency {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG"
+            | "RWF" | "UGX" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// e.g. 1250 EUR is "12.50 EUR"
+pub fn format_price(price_minor: i64, currency: &str) -> String {
+    format!("{} {currency}", format_amount(price_minor, currency))
+}
+
+/// e.g. 1250 EUR is "12.50", the inverse of `parse_price`.
+pub fn format_amount(price_minor: i64, currency: &str) -> String {
+    let exponent = currency_exponent(currency);
+    if exponent == 0 {
+        return price_minor.to_string();
+    }
+
+    let unit = 10i64.pow(exponent);
+    format!("{}.{:0width$}", price_minor / unit, price_minor % unit, width = exponent as usize)
+}
+
+/// Parses a price like "12.5" into the smallest unit of the currency.
+/// None if it isn't a valid, non-negative amount of that currency.
+pub fn parse_price(amount: &str, currency: &str) -> Option<i64> {
+    let exponent = currency_exponent(currency) as usize;
+    let (whole, fraction) = amount.trim().split_once(['.', ',']).unwrap_or((amount.trim(), ""));
+
+    if whole.is_empty() || fraction.len() > exponent
+        || !whole.chars().chain(fraction.chars()).all(|char| char.is_ascii_digit()) {
+        return None;
+    }
+
+    let whole: i64 = whole.parse().ok()?;
+    let fraction: i64 = format!("{fraction:0<exponent$}").parse().unwrap_or(0);
+
+    whole.checked_mul(10i64.pow(exponent as u32))?.checked_add(fraction)
+}
+
 #[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone)]
 #[diesel(sql_type = crate::schema::sql_types::PlantLocation)]
 pub enum PlantLocation {
@@ -65,6 +272,534 @@ impl FromSql<crate::schema::sql_types::PlantLocation, Pg> for PlantLocation {
     }
 }
 
+/// Which part of the plant is on a picture, helps with recognising it.
+#[derive(Debug, Default, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::PlantOrgan)]
+#[serde(rename_all = "lowercase")]
+pub enum Organ {
+    #[default]
+    Auto,
+    Leaf,
+    Flower,
+    Fruit,
+    Bark,
+}
+
+impl Organ {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Organ::Auto => "auto",
+            Organ::Leaf => "leaf",
+            Organ::Flower => "flower",
+            Organ::Fruit => "fruit",
+            Organ::Bark => "bark",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::PlantOrgan, Pg> for Organ {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::PlantOrgan, Pg> for Organ {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"auto" => Ok(Organ::Auto),
+            b"leaf" => Ok(Organ::Leaf),
+            b"flower" => Ok(Organ::Flower),
+            b"fruit" => Ok(Organ::Fruit),
+            b"bark" => Ok(Organ::Bark),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::WateringFrequency)]
+#[serde(rename_all = "snake_case")]
+pub enum WateringFrequency {
+    Daily,
+    TwiceWeekly,
+    Weekly,
+    Biweekly,
+    Monthly,
+}
+
+impl WateringFrequency {
+    pub const ALL: &'static [WateringFrequency] = &[
+        WateringFrequency::Daily,
+        WateringFrequency::TwiceWeekly,
+        WateringFrequency::Weekly,
+        WateringFrequency::Biweekly,
+        WateringFrequency::Monthly,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WateringFrequency::Daily => "daily",
+            WateringFrequency::TwiceWeekly => "twice_weekly",
+            WateringFrequency::Weekly => "weekly",
+            WateringFrequency::Biweekly => "biweekly",
+            WateringFrequency::Monthly => "monthly",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WateringFrequency::Daily => "Daily",
+            WateringFrequency::TwiceWeekly => "Twice a week",
+            WateringFrequency::Weekly => "Weekly",
+            WateringFrequency::Biweekly => "Every two weeks",
+            WateringFrequency::Monthly => "Monthly",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::WateringFrequency, Pg> for WateringFrequency {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::WateringFrequency, Pg> for WateringFrequency {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"daily" => Ok(WateringFrequency::Daily),
+            b"twice_weekly" => Ok(WateringFrequency::TwiceWeekly),
+            b"weekly" => Ok(WateringFrequency::Weekly),
+            b"biweekly" => Ok(WateringFrequency::Biweekly),
+            b"monthly" => Ok(WateringFrequency::Monthly),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::LightRequirement)]
+#[serde(rename_all = "snake_case")]
+pub enum LightRequirement {
+    FullSun,
+    PartialSun,
+    BrightIndirect,
+    LowLight,
+}
+
+impl LightRequirement {
+    pub const ALL: &'static [LightRequirement] = &[
+        LightRequirement::FullSun,
+        LightRequirement::PartialSun,
+        LightRequirement::BrightIndirect,
+        LightRequirement::LowLight,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LightRequirement::FullSun => "full_sun",
+            LightRequirement::PartialSun => "partial_sun",
+            LightRequirement::BrightIndirect => "bright_indirect",
+            LightRequirement::LowLight => "low_light",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LightRequirement::FullSun => "Full sun",
+            LightRequirement::PartialSun => "Partial sun",
+            LightRequirement::BrightIndirect => "Bright, indirect light",
+            LightRequirement::LowLight => "Low light",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::LightRequirement, Pg> for LightRequirement {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::LightRequirement, Pg> for LightRequirement {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"full_sun" => Ok(LightRequirement::FullSun),
+            b"partial_sun" => Ok(LightRequirement::PartialSun),
+            b"bright_indirect" => Ok(LightRequirement::BrightIndirect),
+            b"low_light" => Ok(LightRequirement::LowLight),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::PropagationMethod)]
+#[serde(rename_all = "snake_case")]
+pub enum PropagationMethod {
+    Seed,
+    Cutting,
+    Division,
+    Layering,
+    Offset,
+    Grafting,
+}
+
+impl PropagationMethod {
+    pub const ALL: &'static [PropagationMethod] = &[
+        PropagationMethod::Seed,
+        PropagationMethod::Cutting,
+        PropagationMethod::Division,
+        PropagationMethod::Layering,
+        PropagationMethod::Offset,
+        PropagationMethod::Grafting,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PropagationMethod::Seed => "seed",
+            PropagationMethod::Cutting => "cutting",
+            PropagationMethod::Division => "division",
+            PropagationMethod::Layering => "layering",
+            PropagationMethod::Offset => "offset",
+            PropagationMethod::Grafting => "grafting",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PropagationMethod::Seed => "Seeds",
+            PropagationMethod::Cutting => "Cuttings",
+            PropagationMethod::Division => "Division",
+            PropagationMethod::Layering => "Layering",
+            PropagationMethod::Offset => "Offsets",
+            PropagationMethod::Grafting => "Grafting",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::PropagationMethod, Pg> for PropagationMethod {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::PropagationMethod, Pg> for PropagationMethod {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"seed" => Ok(PropagationMethod::Seed),
+            b"cutting" => Ok(PropagationMethod::Cutting),
+            b"division" => Ok(PropagationMethod::Division),
+            b"layering" => Ok(PropagationMethod::Layering),
+            b"offset" => Ok(PropagationMethod::Offset),
+            b"grafting" => Ok(PropagationMethod::Grafting),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+/// Main growing or flowering season.
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::PlantSeason)]
+#[serde(rename_all = "snake_case")]
+pub enum PlantSeason {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+    AllYear,
+}
+
+impl PlantSeason {
+    pub const ALL: &'static [PlantSeason] = &[
+        PlantSeason::Spring,
+        PlantSeason::Summer,
+        PlantSeason::Autumn,
+        PlantSeason::Winter,
+        PlantSeason::AllYear,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlantSeason::Spring => "spring",
+            PlantSeason::Summer => "summer",
+            PlantSeason::Autumn => "autumn",
+            PlantSeason::Winter => "winter",
+            PlantSeason::AllYear => "all_year",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlantSeason::Spring => "Spring",
+            PlantSeason::Summer => "Summer",
+            PlantSeason::Autumn => "Autumn",
+            PlantSeason::Winter => "Winter",
+            PlantSeason::AllYear => "All year",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::PlantSeason, Pg> for PlantSeason {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::PlantSeason, Pg> for PlantSeason {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"spring" => Ok(PlantSeason::Spring),
+            b"summer" => Ok(PlantSeason::Summer),
+            b"autumn" => Ok(PlantSeason::Autumn),
+            b"winter" => Ok(PlantSeason::Winter),
+            b"all_year" => Ok(PlantSeason::AllYear),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+/// How two plants in the similar plants graph are related.
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::PlantRelation)]
+#[serde(rename_all = "snake_case")]
+pub enum PlantRelation {
+    SameGenus,
+    LookAlike,
+    /// Grows well next to the other plant.
+    Companion,
+}
+
+impl PlantRelation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlantRelation::SameGenus => "same_genus",
+            PlantRelation::LookAlike => "look_alike",
+            PlantRelation::Companion => "companion",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlantRelation::SameGenus => "Same genus",
+            PlantRelation::LookAlike => "Looks alike",
+            PlantRelation::Companion => "Companion plant",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::PlantRelation, Pg> for PlantRelation {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::PlantRelation, Pg> for PlantRelation {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"same_genus" => Ok(PlantRelation::SameGenus),
+            b"look_alike" => Ok(PlantRelation::LookAlike),
+            b"companion" => Ok(PlantRelation::Companion),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::TaxonRank)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxonRank {
+    Family,
+    Genus,
+}
+
+impl TaxonRank {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaxonRank::Family => "family",
+            TaxonRank::Genus => "genus",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaxonRank::Family => "Family",
+            TaxonRank::Genus => "Genus",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::TaxonRank, Pg> for TaxonRank {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::TaxonRank, Pg> for TaxonRank {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"family" => Ok(TaxonRank::Family),
+            b"genus" => Ok(TaxonRank::Genus),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+/// What happens to listings of a restricted plant.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::RestrictionLevel)]
+#[serde(rename_all = "snake_case")]
+pub enum RestrictionLevel {
+    /// The listing is created, but the author is told about the restriction.
+    Warn,
+    /// The listing can't be created.
+    Block,
+}
+
+impl RestrictionLevel {
+    pub const ALL: &'static [RestrictionLevel] = &[RestrictionLevel::Warn, RestrictionLevel::Block];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestrictionLevel::Warn => "warn",
+            RestrictionLevel::Block => "block",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RestrictionLevel::Warn => "Warn",
+            RestrictionLevel::Block => "Block",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::RestrictionLevel, Pg> for RestrictionLevel {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::RestrictionLevel, Pg> for RestrictionLevel {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"warn" => Ok(RestrictionLevel::Warn),
+            b"block" => Ok(RestrictionLevel::Block),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::PlantEditStatus)]
+#[serde(rename_all = "snake_case")]
+pub enum PlantEditStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl PlantEditStatus {
+    pub const ALL: &'static [PlantEditStatus] = &[
+        PlantEditStatus::Pending,
+        PlantEditStatus::Approved,
+        PlantEditStatus::Rejected,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlantEditStatus::Pending => "pending",
+            PlantEditStatus::Approved => "approved",
+            PlantEditStatus::Rejected => "rejected",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlantEditStatus::Pending => "Pending",
+            PlantEditStatus::Approved => "Approved",
+            PlantEditStatus::Rejected => "Rejected",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::PlantEditStatus, Pg> for PlantEditStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::PlantEditStatus, Pg> for PlantEditStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"pending" => Ok(PlantEditStatus::Pending),
+            b"approved" => Ok(PlantEditStatus::Approved),
+            b"rejected" => Ok(PlantEditStatus::Rejected),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+/// How much the identified plant of a listing can be trusted.
+#[derive(Debug, PartialEq, Eq, FromSqlRow, AsExpression, Serialize, Deserialize, Clone, Copy)]
+#[diesel(sql_type = crate::schema::sql_types::IdentificationState)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentificationState {
+    /// Set from plant recognition, nobody looked at it yet.
+    AiSuggested,
+    AuthorConfirmed,
+    /// Enough other users voted for the plant.
+    CommunityVerified,
+    /// Other users voted for different plants.
+    Disputed,
+}
+
+impl IdentificationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdentificationState::AiSuggested => "ai_suggested",
+            IdentificationState::AuthorConfirmed => "author_confirmed",
+            IdentificationState::CommunityVerified => "community_verified",
+            IdentificationState::Disputed => "disputed",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            IdentificationState::AiSuggested => "Suggested by plant recognition",
+            IdentificationState::AuthorConfirmed => "Confirmed by the author",
+            IdentificationState::CommunityVerified => "Verified by the community",
+            IdentificationState::Disputed => "Disputed",
+        }
+    }
+}
+
+impl ToSql<crate::schema::sql_types::IdentificationState, Pg> for IdentificationState {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::IdentificationState, Pg> for IdentificationState {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"ai_suggested" => Ok(IdentificationState::AiSuggested),
+            b"author_confirmed" => Ok(IdentificationState::AuthorConfirmed),
+            b"community_verified" => Ok(IdentificationState::CommunityVerified),
+            b"disputed" => Ok(IdentificationState::Disputed),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
 #[derive(Queryable, Selectable, Identifiable, Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[diesel(table_name = crate::schema::users)]
 pub struct User {
@@ -72,7 +807,7 @@ pub struct User {
     pub location: Option<Point>,
 }
 
-#[derive(Insertable, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Insertable, Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
 #[diesel(table_name = crate::schema::listings)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct InsertListing {
@@ -80,8 +815,27 @@ pub struct InsertListing {
     pub description: String,
     pub author: Uuid,
     pub listing_type: ListingType,
+    /// Overridden for giveaways and swaps, see `ListingType::implied_tradeable`.
     pub tradeable: Option<bool>,
-    pub thumbnail: Uuid
+    pub thumbnail: Uuid,
+    pub identified_plant: Option<Uuid>,
+    /// A mature plant if None
+    pub propagation_form: Option<PropagationForm>,
+    /// 1 if None
+    pub quantity: Option<i32>,
+    pub pot_size_cm: Option<i16>,
+    /// Fixed if None but there is a price.
+    pub price_mode: Option<PriceMode>,
+    pub price_minor: Option<i64>,
+    pub currency: Option<String>,
+    pub price_negotiable: Option<bool>,
+    /// Possible if None
+    pub local_pickup: Option<bool>,
+    pub shipping: Option<bool>,
+    pub shipping_cost_minor: Option<i64>,
+    pub shipping_currency: Option<String>,
+    pub meet_halfway: Option<bool>,
+    pub max_pickup_distance_km: Option<f64>,
 }
 
 #[derive(Queryable, Selectable, Identifiable, Associations, Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -99,6 +853,26 @@ pub struct Listing {
     pub thumbnail: Uuid,
     pub tradeable: bool,
     pub identified_plant: Option<Uuid>,
+    /// None if there's no identified plant.
+    pub identification_state: Option<IdentificationState>,
+    pub propagation_form: PropagationForm,
+    /// How many cuttings, seeds, ... are offered or wanted.
+    pub quantity: i32,
+    /// Diameter of the pot the plant comes in.
+    pub pot_size_cm: Option<i16>,
+    pub price_mode: Option<PriceMode>,
+    /// In the smallest unit of the currency, only set for fixed prices.
+    pub price_minor: Option<i64>,
+    pub currency: Option<String>,
+    pub price_negotiable: bool,
+    pub local_pickup: bool,
+    pub shipping: bool,
+    /// In the smallest unit of the shipping currency, unknown if None.
+    pub shipping_cost_minor: Option<i64>,
+    pub shipping_currency: Option<String>,
+    pub meet_halfway: bool,
+    /// How far the author lets people come from to pick it up or meet.
+    pub max_pickup_distance_km: Option<f64>,
 }
 
 /// fields set to None will not be updated.
@@ -116,6 +890,69 @@ pub struct ListingUpdate {
     pub thumbnail: Option<Uuid>,
     pub tradeable: Option<bool>,
     pub identified_plant: Option<Uuid>,
+    pub propagation_form: Option<PropagationForm>,
+    pub quantity: Option<i32>,
+    pub pot_size_cm: Option<i16>,
+    /// The price and currency are removed when switching to a mode without a price.
+    pub price_mode: Option<PriceMode>,
+    pub price_minor: Option<i64>,
+    pub currency: Option<String>,
+    pub price_negotiable: Option<bool>,
+    pub local_pickup: Option<bool>,
+    /// The shipping cost is removed when shipping is turned off.
+    pub shipping: Option<bool>,
+    pub shipping_cost_minor: Option<i64>,
+    pub shipping_currency: Option<String>,
+    pub meet_halfway: Option<bool>,
+    pub max_pickup_distance_km: Option<f64>,
+}
+
+/// The price a listing had from `changed_at` on.
+#[derive(Queryable, Selectable, Identifiable, Associations, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::listing_price_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(belongs_to(Listing))]
+pub struct PriceChange {
+    pub id: Uuid,
+    pub listing_id: Uuid,
+    pub price_mode: Option<PriceMode>,
+    pub price_minor: Option<i64>,
+    pub currency: Option<String>,
+    pub price_negotiable: bool,
+    pub changed_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = crate::schema::listing_price_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct InsertPriceChange {
+    pub listing_id: Uuid,
+    pub price_mode: Option<PriceMode>,
+    pub price_minor: Option<i64>,
+    pub currency: Option<String>,
+    pub price_negotiable: bool,
+}
+
+impl InsertPriceChange {
+    pub fn of(listing: &Listing) -> Self {
+        Self {
+            listing_id: listing.id,
+            price_mode: listing.price_mode,
+            price_minor: listing.price_minor,
+            currency: listing.currency.clone(),
+            price_negotiable: listing.price_negotiable,
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::identification_votes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct IdentificationVote {
+    pub listing_id: Uuid,
+    pub voter: Uuid,
+    pub plant: Uuid,
+    pub voted_at: chrono::NaiveDateTime,
 }
 
 #[derive(Queryable, Selectable, Identifiable, Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -126,6 +963,35 @@ pub struct Image {
     pub file_key: Uuid,
     pub uploaded_by_user: Option<Uuid>,
     pub upload_date: chrono::NaiveDateTime,
+    /// Set while a direct upload hasn't been confirmed yet.
+    pub pending_until: Option<chrono::NaiveDateTime>,
+    /// Perceptual hash, None if the image couldn't be decoded.
+    pub phash: Option<i64>,
+    pub size_bytes: i64,
+    pub blurhash: Option<String>,
+    pub dominant_color: Option<String>,
+    /// sha256 of the image data, None for old images until it's needed.
+    pub content_hash: Option<String>,
+}
+
+/// What to show while the image itself is still loading.
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::images)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ImagePlaceholder {
+    pub blurhash: Option<String>,
+    pub dominant_color: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Serialize, Debug, PartialEq, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ListingWithPlaceholder {
+    #[diesel(embed)]
+    #[serde(flatten)]
+    pub listing: Listing,
+    /// None if there is no image row for the thumbnail.
+    #[diesel(embed)]
+    pub thumbnail_placeholder: Option<ImagePlaceholder>,
 }
 
 #[derive(Insertable, Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -134,6 +1000,147 @@ pub struct Image {
 pub struct InsertImage {
     pub file_key: Uuid,
     pub uploaded_by_user: Option<Uuid>,
+    pub pending_until: Option<chrono::NaiveDateTime>,
+    pub phash: Option<i64>,
+    pub size_bytes: i64,
+    pub blurhash: Option<String>,
+    pub dominant_color: Option<String>,
+    pub content_hash: Option<String>,
+    /// Where a direct upload gets PUT to, see `Backend::create_direct_upload`.
+    pub staging_key: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct UploadQuota {
+    pub max_bytes: i64,
+    pub max_images: i64,
+    pub max_per_hour: i64,
+}
+
+/// Admin set override of the configured upload quota,
+/// fields set to None use the configured default.
+#[derive(Queryable, Selectable, Identifiable, Insertable, AsChangeset, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::upload_quotas)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(user_id))]
+#[diesel(treat_none_as_null = true)]
+pub struct UploadQuotaOverride {
+    pub user_id: Uuid,
+    pub max_bytes: Option<i64>,
+    pub max_images: Option<i64>,
+    pub max_per_hour: Option<i64>,
+}
+
+impl UploadQuotaOverride {
+    pub fn apply_to(&self, quota: UploadQuota) -> UploadQuota {
+        UploadQuota {
+            max_bytes: self.max_bytes.unwrap_or(quota.max_bytes),
+            max_images: self.max_images.unwrap_or(quota.max_images),
+            max_per_hour: self.max_per_hour.unwrap_or(quota.max_per_hour),
+        }
+    }
+}
+
+#[derive(QueryableByName, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct UploadUsage {
+    #[diesel(sql_type = diesel::sql_types::Int8)]
+    pub total_bytes: i64,
+    #[diesel(sql_type = diesel::sql_types::Int8)]
+    pub image_count: i64,
+    #[diesel(sql_type = diesel::sql_types::Int8)]
+    pub uploads_last_hour: i64,
+}
+
+/// What the author filled in so far, see `InsertListing` for the meaning
+/// of the fields. Saving a draft replaces all of them.
+#[derive(Queryable, Selectable, Insertable, AsChangeset, Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::listing_drafts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(treat_none_as_null = true)]
+pub struct DraftFields {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub listing_type: Option<ListingType>,
+    pub tradeable: Option<bool>,
+    pub identified_plant: Option<Uuid>,
+    pub propagation_form: Option<PropagationForm>,
+    pub quantity: Option<i32>,
+    pub pot_size_cm: Option<i16>,
+    pub price_mode: Option<PriceMode>,
+    pub price_minor: Option<i64>,
+    pub currency: Option<String>,
+    pub price_negotiable: Option<bool>,
+    pub local_pickup: Option<bool>,
+    pub shipping: Option<bool>,
+    pub shipping_cost_minor: Option<i64>,
+    pub shipping_currency: Option<String>,
+    pub meet_halfway: Option<bool>,
+    pub max_pickup_distance_km: Option<f64>,
+}
+
+/// A listing that isn't published yet, only its author can see it.
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::listing_drafts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ListingDraft {
+    pub id: Uuid,
+    pub author: Uuid,
+    #[diesel(embed)]
+    #[serde(flatten)]
+    pub fields: DraftFields,
+    /// Published as soon as this has passed.
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    /// Why publishing it at `publish_at` didn't work.
+    pub publish_error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::listing_draft_pictures)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DraftPicture {
+    pub draft_id: Uuid,
+    pub image: Uuid,
+    pub position: i16,
+}
+
+#[derive(Insertable, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::listing_pictures)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ListingPicture {
+    pub listing_id: Uuid,
+    pub image: Uuid,
+    pub position: i16,
+}
+
+#[derive(Queryable, QueryableByName, Selectable, Identifiable, Associations, Serialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::suspected_duplicates)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(belongs_to(Listing))]
+pub struct SuspectedDuplicate {
+    pub id: Uuid,
+    pub listing_id: Uuid,
+    pub image: Uuid,
+    pub matched_image: Uuid,
+    pub distance: i16,
+    pub flagged_at: chrono::NaiveDateTime,
+}
+
+/// Cached result of a plant recognition, `results` holds the list of `RankedPlant`s.
+#[derive(Queryable, Selectable, Identifiable, Insertable, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::recognitions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Recognition {
+    pub id: Uuid,
+    pub cache_key: String,
+    pub images: Vec<Uuid>,
+    pub location: Option<Point>,
+    pub results: serde_json::Value,
+    pub created_at: chrono::NaiveDateTime,
+    pub organs: Vec<Organ>,
+    pub language: String,
+    pub region: Option<Point>,
 }
 
 #[derive(Queryable, Selectable, Identifiable, Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -148,6 +1155,95 @@ pub struct Plant {
     pub location: Option<PlantLocation>,
     pub produces_fruit: Option<bool>,
     pub description: String,
+    pub watering: Option<WateringFrequency>,
+    pub light: Option<LightRequirement>,
+    /// Coldest USDA hardiness zone the plant survives outdoors.
+    pub hardiness_zone: Option<i16>,
+    pub toxic_to_pets: Option<bool>,
+    pub propagation_methods: Vec<PropagationMethod>,
+    pub season: Option<PlantSeason>,
+    pub genus: Option<Uuid>,
+}
+
+impl Plant {
+    pub fn care(&self) -> PlantCare {
+        PlantCare {
+            watering: self.watering,
+            light: self.light,
+            hardiness_zone: self.hardiness_zone,
+            toxic_to_pets: self.toxic_to_pets,
+            propagation_methods: Some(self.propagation_methods.clone()).filter(|methods| !methods.is_empty()),
+            season: self.season,
+        }
+    }
+}
+
+/// Care information of a plant, None if unknown. As changeset
+/// fields set to None stay as they are.
+#[derive(AsChangeset, Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::plants)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PlantCare {
+    pub watering: Option<WateringFrequency>,
+    pub light: Option<LightRequirement>,
+    pub hardiness_zone: Option<i16>,
+    pub toxic_to_pets: Option<bool>,
+    pub propagation_methods: Option<Vec<PropagationMethod>>,
+    pub season: Option<PlantSeason>,
+}
+
+impl PlantCare {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Only the fields that differ from `current`.
+    pub fn changes_to(self, current: &PlantCare) -> PlantCare {
+        fn changed<T: PartialEq>(new: Option<T>, current: &Option<T>) -> Option<T> {
+            new.filter(|new| Some(new) != current.as_ref())
+        }
+
+        // an empty list removes the known methods
+        let propagation_methods = match (self.propagation_methods, &current.propagation_methods) {
+            (Some(methods), None) if methods.is_empty() => None,
+            (methods, current_methods) => changed(methods, current_methods),
+        };
+
+        PlantCare {
+            watering: changed(self.watering, &current.watering),
+            light: changed(self.light, &current.light),
+            hardiness_zone: changed(self.hardiness_zone, &current.hardiness_zone),
+            toxic_to_pets: changed(self.toxic_to_pets, &current.toxic_to_pets),
+            propagation_methods,
+            season: changed(self.season, &current.season),
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Identifiable, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::plant_edits)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PlantEdit {
+    pub id: Uuid,
+    pub plant_id: Uuid,
+    pub author: Uuid,
+    /// A `PlantCare`
+    pub changes: serde_json::Value,
+    pub status: PlantEditStatus,
+    pub created_at: chrono::NaiveDateTime,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<chrono::NaiveDateTime>,
+}
+
+/// Edge of the similar plants graph, `plant_a` is always the smaller id.
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::plant_similarity)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PlantSimilarity {
+    pub plant_a: Uuid,
+    pub plant_b: Uuid,
+    pub relation: PlantRelation,
+    pub weight: f32,
 }
 
 #[derive(Insertable, Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -161,6 +1257,77 @@ pub struct InsertPlant {
     pub location: Option<PlantLocation>,
     pub produces_fruit: Option<bool>,
     pub description: String,
+    pub genus: Option<Uuid>,
+}
+
+/// Common name of a plant in one language.
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::plant_names)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PlantName {
+    pub plant_id: Uuid,
+    /// ISO 639-1 code
+    pub lang: String,
+    pub name: String,
+    /// The name shown in this language, there's at most one per language.
+    pub is_primary: bool,
+}
+
+/// A family or genus, plants belong to a genus.
+#[derive(Queryable, Selectable, Identifiable, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::taxa)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Taxon {
+    pub id: Uuid,
+    pub rank: TaxonRank,
+    pub name: String,
+    /// The family of a genus.
+    pub parent: Option<Uuid>,
+}
+
+/// Rule against trading a species, or all plants of a genus or family,
+/// e.g. because it's CITES listed or invasive in a region.
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::trade_restrictions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TradeRestriction {
+    pub id: Uuid,
+    /// Exactly one of `powo_id` and `taxon_id` is set.
+    pub powo_id: Option<String>,
+    pub taxon_id: Option<Uuid>,
+    pub level: RestrictionLevel,
+    pub reason: String,
+    /// The rule applies everywhere without a region.
+    pub region_name: Option<String>,
+    pub region: Option<Polygon<Point>>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl TradeRestriction {
+    /// Explanation for the author of a listing.
+    pub fn message(&self) -> String {
+        let action = match self.level {
+            RestrictionLevel::Warn => "Check the rules before trading this plant",
+            RestrictionLevel::Block => "This plant can't be traded",
+        };
+
+        match &self.region_name {
+            Some(region) => format!("{action} in {region}: {}", self.reason),
+            None => format!("{action}: {}", self.reason),
+        }
+    }
+}
+
+#[derive(Insertable, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::trade_restrictions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct InsertTradeRestriction {
+    pub powo_id: Option<String>,
+    pub taxon_id: Option<Uuid>,
+    pub level: RestrictionLevel,
+    pub reason: String,
+    pub region_name: Option<String>,
+    pub region: Option<Polygon<Point>>,
 }
 
 #[derive(Identifiable, Queryable, Selectable, Insertable, PartialEq, Clone)]
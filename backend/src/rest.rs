@@ -1,14 +1,53 @@
-use axum::Router;
+use axum::{http::{header, HeaderMap}, Router};
+use tracing::error;
 
-use crate::AppState;
+use crate::{auth::AuthSession, backend::Backend, AppState};
 
+mod admin;
+mod drafts;
 mod listings;
 mod pictures;
 mod plants;
+mod taxa;
+mod users;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .nest("/listing", listings::router())
+        .nest("/draft", drafts::router())
         .nest("/picture", pictures::router())
         .nest("/plant", plants::router())
+        .nest("/taxon", taxa::router())
+        .nest("/user", users::router())
+        .nest("/admin", admin::router())
+}
+
+/// First language of the Accept-Language header as ISO 639-1 code,
+/// "en" if there is none we understand.
+pub(crate) fn request_language(headers: &HeaderMap) -> String {
+    headers.get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .and_then(|language| language.split(';').next())
+        .and_then(|language| language.trim().split('-').next())
+        .filter(|language| language.len() == 2 && language.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// Language plant names are shown in: the one from the user's profile,
+/// or else the one from the Accept-Language header.
+pub(crate) async fn user_language(backend: &Backend, auth_session: &AuthSession, headers: &HeaderMap) -> String {
+    let Some(user) = &auth_session.user else {
+        return request_language(headers);
+    };
+
+    match backend.get_preferred_language(user.claims.user_id).await {
+        Ok(Some(language)) => language,
+        Ok(None) => request_language(headers),
+        Err(err) => {
+            error!(?err, "Error while getting the user's preferred language");
+            request_language(headers)
+        }
+    }
 }
@@ -0,0 +1,390 @@
+use std::sync::Arc;
+
+use axum::{extract::{Path, State}, http::StatusCode, response::{IntoResponse, Response}, routing::{delete, get, post, put}, Json, Router};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    auth::{is_admin, AuthSession},
+    backend::{Backend, BackendError, RegionBounds},
+    config::AppConfig,
+    models::{InsertTradeRestriction, Plant, PlantEdit, PlantRelation, RestrictionLevel, Taxon, TradeRestriction, UploadQuota, UploadQuotaOverride, UploadUsage},
+    AppState,
+};
+
+use super::plants::recognition_error_response;
+
+/// Everything in here is only accessible to users with the admin role.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/quota/:user_id", get(get_upload_quota)
+            .put(set_upload_quota).delete(remove_upload_quota))
+        .route("/recognitions/:id/rerun", post(rerun_recognition))
+        .route("/recognitions/quota", get(get_recognition_quota))
+        .route("/plant-edits", get(get_pending_plant_edits))
+        .route("/plant-edits/:id/approve", post(approve_plant_edit))
+        .route("/plant-edits/:id/reject", post(reject_plant_edit))
+        .route("/plant-similarity", put(set_plant_similarity).delete(remove_plant_similarity))
+        .route("/trade-restrictions", get(get_trade_restrictions).post(add_trade_restriction))
+        .route("/trade-restrictions/:id", delete(remove_trade_restriction))
+}
+
+#[derive(Serialize)]
+struct UploadQuotaResponse {
+    pub quota: UploadQuota,
+    pub usage: UploadUsage,
+}
+
+async fn get_upload_quota(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.get_upload_quota(user_id).await {
+        Ok((quota, usage)) => Json(UploadQuotaResponse { quota, usage }).into_response(),
+        Err(err) => {
+            error!(?err, ?user_id, "Error while getting upload quota");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+/// Fields left out use the configured default.
+#[derive(Deserialize)]
+struct UploadQuotaBody {
+    #[serde(default)]
+    pub max_bytes: Option<i64>,
+    #[serde(default)]
+    pub max_images: Option<i64>,
+    #[serde(default)]
+    pub max_per_hour: Option<i64>,
+}
+
+async fn set_upload_quota(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(user_id): Path<Uuid>,
+    Json(body): Json<UploadQuotaBody>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let quota_override = UploadQuotaOverride {
+        user_id,
+        max_bytes: body.max_bytes,
+        max_images: body.max_images,
+        max_per_hour: body.max_per_hour,
+    };
+
+    match backend.set_upload_quota_override(&quota_override).await {
+        Ok(quota_override) => (StatusCode::OK, Json(quota_override)).into_response(),
+        Err(err) => {
+            error!(?err, ?user_id, "Error while setting upload quota");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+async fn remove_upload_quota(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.remove_upload_quota_override(user_id).await {
+        Ok(Some(quota_override)) => (StatusCode::OK, Json(quota_override)).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "User has no quota override").into_response(),
+        Err(err) => {
+            error!(?err, ?user_id, "Error while removing upload quota");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+/// Asks the plant recogniser again instead of using the cached result.
+async fn rerun_recognition(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.rerun_recognition(id).await {
+        Ok(Some(recognition)) => (StatusCode::OK, Json(recognition)).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Recognition not found").into_response(),
+        Err(BackendError::PlantRecognition(err)) => recognition_error_response(err),
+        Err(err) => {
+            error!(?err, ?id, "Error while re-running plant recognition");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Plant analysis failed").into_response()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RecognitionQuotaResponse {
+    /// None until the first recognition since startup, or if the recogniser has no quota
+    pub remaining_requests: Option<i64>,
+}
+
+/// How many plantnet requests are left for today.
+async fn get_recognition_quota(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let remaining_requests = backend.plant_recognition.remaining_requests();
+
+    Json(RecognitionQuotaResponse { remaining_requests }).into_response()
+}
+
+#[derive(Serialize)]
+struct PendingPlantEdit {
+    #[serde(flatten)]
+    pub edit: PlantEdit,
+    pub plant: Plant,
+}
+
+async fn get_pending_plant_edits(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.get_pending_plant_edits().await {
+        Ok(edits) => {
+            let edits: Vec<_> = edits.into_iter()
+                .map(|(edit, plant)| PendingPlantEdit { edit, plant })
+                .collect();
+            Json(edits).into_response()
+        }
+        Err(err) => {
+            error!(?err, "Error while getting pending plant edits");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+async fn approve_plant_edit(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    review_plant_edit(auth_session, backend, config, id, true).await
+}
+
+async fn reject_plant_edit(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    review_plant_edit(auth_session, backend, config, id, false).await
+}
+
+async fn review_plant_edit(
+    auth_session: AuthSession,
+    backend: Backend,
+    config: Arc<AppConfig>,
+    id: Uuid,
+    approve: bool,
+) -> Response {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let reviewer = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    match backend.review_plant_edit(id, reviewer, approve).await {
+        Ok(Some(edit)) => Json(edit).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "No pending plant edit with this id").into_response(),
+        Err(err) => {
+            error!(?err, ?id, approve, "Error while reviewing plant edit");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+/// An edge of the similar plants graph, the order of the plants doesn't matter.
+#[derive(Deserialize)]
+struct PlantSimilarityBody {
+    pub plant_a: Uuid,
+    pub plant_b: Uuid,
+    pub relation: PlantRelation,
+    /// Ignored when removing, 1 if left out.
+    #[serde(default)]
+    pub weight: Option<f32>,
+}
+
+async fn set_plant_similarity(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Json(body): Json<PlantSimilarityBody>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let weight = body.weight.unwrap_or(1.0);
+
+    match backend.set_plant_similarity(body.plant_a, body.plant_b, body.relation, weight).await {
+        Ok(similarity) => Json(similarity).into_response(),
+        Err(BackendError::InvalidPlantSimilarity(message)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+        }
+        Err(BackendError::PlantNotFound(_)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, "Unknown plant").into_response()
+        }
+        Err(err) => {
+            error!(?err, plant_a = ?body.plant_a, plant_b = ?body.plant_b, "Error while setting plant similarity");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+async fn remove_plant_similarity(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Json(body): Json<PlantSimilarityBody>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.remove_plant_similarity(body.plant_a, body.plant_b, body.relation).await {
+        Ok(Some(similarity)) => Json(similarity).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "The plants aren't related this way").into_response(),
+        Err(err) => {
+            error!(?err, plant_a = ?body.plant_a, plant_b = ?body.plant_b, "Error while removing plant similarity");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TradeRestrictionResponse {
+    #[serde(flatten)]
+    pub restriction: TradeRestriction,
+    /// The genus or family, unless the restriction is for a single species.
+    pub taxon: Option<Taxon>,
+}
+
+async fn get_trade_restrictions(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.get_trade_restrictions().await {
+        Ok(restrictions) => {
+            let restrictions: Vec<_> = restrictions.into_iter()
+                .map(|(restriction, taxon)| TradeRestrictionResponse { restriction, taxon })
+                .collect();
+            Json(restrictions).into_response()
+        }
+        Err(err) => {
+            error!(?err, "Error while getting trade restrictions");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+/// Either `powo_id` or `taxon_id` has to be set. Without a region the
+/// restriction applies everywhere.
+#[derive(Deserialize, Debug)]
+struct TradeRestrictionBody {
+    #[serde(default)]
+    pub powo_id: Option<String>,
+    #[serde(default)]
+    pub taxon_id: Option<Uuid>,
+    pub level: RestrictionLevel,
+    pub reason: String,
+    #[serde(default)]
+    pub region_name: Option<String>,
+    #[serde(default)]
+    pub region: Option<RegionBounds>,
+}
+
+async fn add_trade_restriction(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Json(body): Json<TradeRestrictionBody>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let region = match body.region.map(|bounds| bounds.to_polygon()) {
+        Some(None) => return (StatusCode::UNPROCESSABLE_ENTITY, "Invalid region bounds").into_response(),
+        Some(polygon) => polygon,
+        None => None,
+    };
+
+    let restriction = InsertTradeRestriction {
+        powo_id: body.powo_id,
+        taxon_id: body.taxon_id,
+        level: body.level,
+        reason: body.reason,
+        region_name: body.region_name,
+        region,
+    };
+
+    match backend.add_trade_restriction(restriction).await {
+        Ok(restriction) => (StatusCode::CREATED, Json(restriction)).into_response(),
+        Err(BackendError::InvalidTradeRestriction(message)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+        }
+        Err(err) => {
+            error!(?err, "Error while adding trade restriction");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+async fn remove_trade_restriction(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !is_admin(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match backend.remove_trade_restriction(id).await {
+        Ok(Some(restriction)) => Json(restriction).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Trade restriction not found").into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while removing trade restriction");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
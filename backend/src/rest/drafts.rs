@@ -0,0 +1,175 @@
+use axum::{extract::{Path, State}, http::StatusCode, routing::{get, post, put}, Json, Router};
+use axum::response::IntoResponse;
+use axum_login::login_required;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{auth::{AuthSession, AuthState}, backend::{Backend, BackendError}, models::DraftFields, AppState};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_drafts).post(create_draft))
+        .route("/:id", get(get_draft).put(save_draft).delete(delete_draft))
+        .route("/:id/pictures", put(set_draft_pictures))
+        .route("/:id/schedule", put(schedule_draft))
+        .route("/:id/publish", post(publish_draft))
+        .route_layer(login_required!(AuthState, login_url = crate::LOGIN_URL))
+}
+
+fn user_id(auth_session: &AuthSession) -> Uuid {
+    auth_session.user.as_ref().unwrap().claims.user_id
+}
+
+async fn get_drafts(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+) -> impl IntoResponse {
+    match backend.get_drafts(user_id(&auth_session)).await {
+        Ok(drafts) => Json(drafts).into_response(),
+        Err(err) => {
+            error!(?err, "Error while getting drafts");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+async fn create_draft(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Json(fields): Json<DraftFields>,
+) -> impl IntoResponse {
+    match backend.create_draft(user_id(&auth_session), &fields).await {
+        Ok(draft) => (StatusCode::CREATED, Json(draft)).into_response(),
+        Err(BackendError::InvalidListing(message)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+        }
+        Err(err) => {
+            error!(?err, "Error while creating draft");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+async fn get_draft(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match backend.get_draft(id, user_id(&auth_session)).await {
+        Ok(Some(draft)) => Json(draft).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting draft");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+/// Replaces all fields, the ones left out are cleared.
+async fn save_draft(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+    Json(fields): Json<DraftFields>,
+) -> impl IntoResponse {
+    match backend.save_draft(id, user_id(&auth_session), &fields).await {
+        Ok(Some(draft)) => Json(draft).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(BackendError::InvalidListing(message)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?id, "Error while saving draft");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+async fn delete_draft(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match backend.delete_draft(id, user_id(&auth_session)).await {
+        Ok(Some(draft)) => Json(draft).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while deleting draft");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct DraftPicturesBody {
+    /// In order, the first one becomes the thumbnail.
+    pub pictures: Vec<Uuid>,
+}
+
+async fn set_draft_pictures(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<DraftPicturesBody>,
+) -> impl IntoResponse {
+    match backend.set_draft_pictures(id, user_id(&auth_session), &body.pictures).await {
+        Ok(Some(pictures)) => Json(pictures).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(BackendError::ImageNotFound(picture)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, format!("Unknown picture {picture}")).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?id, "Error while setting pictures of draft");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ScheduleBody {
+    /// UTC, null to not publish the draft automatically
+    #[serde(default)]
+    pub publish_at: Option<NaiveDateTime>,
+}
+
+async fn schedule_draft(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<ScheduleBody>,
+) -> impl IntoResponse {
+    match backend.schedule_draft(id, user_id(&auth_session), body.publish_at).await {
+        Ok(Some(draft)) => Json(draft).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while scheduling draft");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+async fn publish_draft(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match backend.publish_draft(id, user_id(&auth_session)).await {
+        Ok(Some(created)) => (StatusCode::CREATED, Json(created)).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(BackendError::TradeRestricted(restrictions)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(restrictions)).into_response()
+        }
+        Err(BackendError::InvalidListing(message)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+        }
+        Err(BackendError::ListingHasNoLocation) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, "Set a location for your account first").into_response()
+        }
+        Err(err) => {
+            error!(?err, ?id, "Error while publishing draft");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while publishing draft").into_response()
+        }
+    }
+}
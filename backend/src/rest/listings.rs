@@ -1,16 +1,20 @@
-use axum::{extract::{Path, State}, http::StatusCode, routing::{get, post}, Json, Router};
+use axum::{extract::{Path, Query, State}, http::StatusCode, routing::{get, post}, Json, Router};
 use serde::Deserialize;
 use tracing::error;
 use uuid::Uuid;
 use axum::response::IntoResponse;
 
-use crate::{auth::AuthSession, backend::Backend, models::{InsertListing, ListingType, ListingUpdate}, AppState};
+use crate::{auth::AuthSession, backend::{Backend, BackendError, ListingFilter}, models::{InsertListing, ListingType, ListingUpdate, PriceMode, PropagationForm}, AppState};
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", post(create_listing).get(get_all_listings))
         .route("/:id", get(get_listing).put(update_listing)
             .patch(update_listing).delete(delete_listing))
+        .route("/:id/identification", get(get_identification)
+            .put(vote_identification).delete(retract_identification_vote))
+        .route("/:id/recommendations", get(get_recommendations))
+        .route("/:id/price-history", get(get_price_history))
 }
 
 
@@ -22,6 +26,38 @@ struct InsertListingBody {
     pub pictures: Vec<Uuid>,
     pub thumbnail: Uuid,
     pub tradeable: bool,
+    #[serde(default)]
+    pub identified_plant: Option<Uuid>,
+    /// A mature plant if left out
+    #[serde(default)]
+    pub propagation_form: Option<PropagationForm>,
+    #[serde(default)]
+    pub quantity: Option<i32>,
+    #[serde(default)]
+    pub pot_size_cm: Option<i16>,
+    /// Fixed if left out but there is a price
+    #[serde(default)]
+    pub price_mode: Option<PriceMode>,
+    /// In the smallest unit of the currency, e.g. cents
+    #[serde(default)]
+    pub price_minor: Option<i64>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub price_negotiable: bool,
+    /// Possible if left out
+    #[serde(default)]
+    pub local_pickup: Option<bool>,
+    #[serde(default)]
+    pub shipping: bool,
+    #[serde(default)]
+    pub shipping_cost_minor: Option<i64>,
+    #[serde(default)]
+    pub shipping_currency: Option<String>,
+    #[serde(default)]
+    pub meet_halfway: bool,
+    #[serde(default)]
+    pub max_pickup_distance_km: Option<f64>,
 }
 
 impl InsertListingBody {
@@ -31,7 +67,21 @@ impl InsertListingBody {
             author,
             listing_type: self.listing_type,
             tradeable: Some(self.tradeable),
-            thumbnail: self.thumbnail
+            thumbnail: self.thumbnail,
+            identified_plant: self.identified_plant,
+            propagation_form: self.propagation_form,
+            quantity: self.quantity,
+            pot_size_cm: self.pot_size_cm,
+            price_mode: self.price_mode,
+            price_minor: self.price_minor,
+            currency: self.currency,
+            price_negotiable: Some(self.price_negotiable),
+            local_pickup: self.local_pickup,
+            shipping: Some(self.shipping),
+            shipping_cost_minor: self.shipping_cost_minor,
+            shipping_currency: self.shipping_currency,
+            meet_halfway: Some(self.meet_halfway),
+            max_pickup_distance_km: self.max_pickup_distance_km,
         }
     }
 }
@@ -39,7 +89,7 @@ impl InsertListingBody {
 async fn create_listing(
     auth_session: AuthSession,
     State(backend): State<Backend>,
-    Json(body): Json<InsertListingBody>
+    Json(mut body): Json<InsertListingBody>
 ) -> impl IntoResponse {
     if body.pictures.is_empty() {
         return (StatusCode::BAD_REQUEST, "Pictures are required").into_response();
@@ -51,11 +101,21 @@ async fn create_listing(
 
     let author_id = auth_session.user.as_ref().unwrap().claims.user_id;
 
+    let pictures = std::mem::take(&mut body.pictures);
     let insert_listing = body.into_insert_listing(author_id);
 
-    match backend.create_listing(insert_listing).await {
-        Ok(listing) => {
-            (StatusCode::CREATED, Json(listing)).into_response()
+    match backend.create_listing(insert_listing, &pictures).await {
+        Ok(created) => {
+            (StatusCode::CREATED, Json(created)).into_response()
+        }
+        Err(BackendError::TradeRestricted(restrictions)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(restrictions)).into_response()
+        }
+        Err(BackendError::InvalidListing(message)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+        }
+        Err(BackendError::ImageNotFound(picture)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, format!("Unknown picture {picture}")).into_response()
         }
         Err(err) => {
             error!(?err, "Database error while creating listing");
@@ -65,13 +125,19 @@ async fn create_listing(
 }
 
 async fn get_all_listings(
-    _auth_session: AuthSession,
-    State(backend): State<Backend>
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Query(mut filter): Query<ListingFilter>,
 ) -> impl IntoResponse {
-    match backend.get_all_listings().await {
+    filter.viewer = auth_session.user.as_ref().map(|user| user.claims.user_id);
+
+    match backend.search_listings(&filter).await {
         Ok(listings) => {
             Json(listings).into_response()
         }
+        Err(BackendError::InvalidListingFilter(message)) => {
+            (StatusCode::BAD_REQUEST, message).into_response()
+        }
         Err(err) => {
             error!(?err, "Error while getting all listings");
             (StatusCode::INTERNAL_SERVER_ERROR, "Error while getting all listings")
@@ -116,6 +182,15 @@ async fn update_listing(
         Ok(None) => {
             (StatusCode::BAD_REQUEST, "Invalid ID").into_response()
         }
+        Err(BackendError::InvalidListing(message)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+        }
+        Err(BackendError::ImageNotFound(picture)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, format!("Unknown picture {picture}")).into_response()
+        }
+        Err(BackendError::TradeRestricted(restrictions)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(restrictions)).into_response()
+        }
         Err(err) => {
             error!(?err, ?listing_update, "Database error while trying to update listing");
             (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
@@ -142,3 +217,113 @@ async fn delete_listing(
         }
     }
 }
+
+async fn get_identification(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let viewer = auth_session.user.as_ref().map(|user| user.claims.user_id);
+
+    match backend.get_identification(id, viewer).await {
+        Ok(Some(identification)) => Json(identification).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting identification of listing");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while getting identification")
+                .into_response()
+        }
+    }
+}
+
+/// Listings to show next to the listing, see `Backend::get_listing_recommendations`.
+async fn get_recommendations(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let viewer = auth_session.user.as_ref().map(|user| user.claims.user_id);
+
+    let listing = match backend.get_listing(id).await {
+        Ok(Some(entry)) => entry.listing,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting listing");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    match backend.get_listing_recommendations(&listing, viewer).await {
+        Ok(recommendations) => Json(recommendations).into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting recommendations for listing");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+/// Every price the listing had, oldest first.
+async fn get_price_history(
+    _auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match backend.get_price_history(id).await {
+        Ok(history) => Json(history).into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting price history of listing");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct IdentificationVoteBody {
+    pub plant: Uuid,
+}
+
+/// Votes for the plant of the listing, the vote of the author confirms it.
+async fn vote_identification(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<IdentificationVoteBody>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user.as_ref() else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    match backend.vote_identification(id, user.claims.user_id, body.plant).await {
+        Ok(Some(listing)) => Json(listing).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(BackendError::PlantNotFound(_)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, "Unknown plant").into_response()
+        }
+        Err(BackendError::TradeRestricted(restrictions)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(restrictions)).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?id, "Error while voting for identification of listing");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while voting").into_response()
+        }
+    }
+}
+
+async fn retract_identification_vote(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user.as_ref() else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    match backend.retract_identification_vote(id, user.claims.user_id).await {
+        Ok(Some(listing)) => Json(listing).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while retracting identification vote");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while retracting vote").into_response()
+        }
+    }
+}
@@ -1,21 +1,26 @@
+use axum::body::Body;
 use axum::extract::Path;
-use axum::http::{header, StatusCode};
-use axum::response::IntoResponse;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{delete, get, post};
 use axum::Json;
 use axum::{extract::State, Router};
 use axum_login::login_required;
 use axum_typed_multipart::{FieldData, TryFromMultipart, TypedMultipart};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 use uuid::Uuid;
 
 use crate::auth::AuthState;
+use crate::backend::image_store::{ByteRange, ImageStoreError};
+use crate::backend::{BackendError, QuotaKind};
 use crate::{auth::AuthSession, backend::Backend, AppState};
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", post(upload_picture))
+        .route("/upload-url", post(create_upload_url))
+        .route("/:id/confirm", post(confirm_upload))
         .route("/:id", delete(remove_picture))
         .route_layer(login_required!(AuthState, login_url = crate::LOGIN_URL))
         .route("/:id", get(get_picture))
@@ -58,6 +63,7 @@ async fn upload_picture(
                 Json(PictureUploadResponse { id })
             ).into_response()
         }
+        Err(BackendError::QuotaExceeded(kind)) => quota_exceeded_response(kind),
         Err(err) => {
             error!(?err, "Couldn't upload image");
             (
@@ -68,26 +74,182 @@ async fn upload_picture(
     }
 }
 
+#[derive(Deserialize)]
+struct UploadUrlRequest {
+    pub content_type: String,
+}
+
+/// Returns a presigned URL the picture can be PUT to directly, bypassing
+/// this server. The upload then needs to be confirmed.
+async fn create_upload_url(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Json(request): Json<UploadUrlRequest>,
+) -> impl IntoResponse {
+    let user_id = auth_session.user.unwrap().claims.user_id;
+
+    match backend.create_direct_upload(user_id, &request.content_type).await {
+        Ok(upload) => (StatusCode::CREATED, Json(upload)).into_response(),
+        Err(BackendError::InvalidImage(reason)) => {
+            (StatusCode::UNSUPPORTED_MEDIA_TYPE, reason).into_response()
+        }
+        Err(BackendError::QuotaExceeded(kind)) => quota_exceeded_response(kind),
+        Err(BackendError::ImageStore(ImageStoreError::Unsupported)) => {
+            (StatusCode::NOT_IMPLEMENTED, "Direct uploads are not supported, upload the picture normally")
+                .into_response()
+        }
+        Err(err) => {
+            error!(?err, "Couldn't create upload url");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Couldn't create upload url").into_response()
+        }
+    }
+}
+
+async fn confirm_upload(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = auth_session.user.unwrap().claims.user_id;
+
+    match backend.confirm_direct_upload(user_id, id).await {
+        Ok(Some(image)) => {
+            (StatusCode::OK, Json(PictureUploadResponse { id: image.file_key })).into_response()
+        }
+        Ok(None) => {
+            (StatusCode::NOT_FOUND, "No pending upload with this id").into_response()
+        }
+        Err(BackendError::InvalidImage(reason)) => {
+            (StatusCode::BAD_REQUEST, reason).into_response()
+        }
+        Err(BackendError::QuotaExceeded(kind)) => quota_exceeded_response(kind),
+        Err(err) => {
+            error!(?err, image_id = ?id, "Couldn't confirm upload");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Couldn't confirm upload").into_response()
+        }
+    }
+}
+
+/// 429 if the user is uploading too fast, 413 if they ran out of space.
+fn quota_exceeded_response(kind: QuotaKind) -> Response {
+    let status = match kind {
+        QuotaKind::UploadRate => StatusCode::TOO_MANY_REQUESTS,
+        QuotaKind::TotalBytes | QuotaKind::ImageCount => StatusCode::PAYLOAD_TOO_LARGE,
+    };
+
+    (status, kind.message()).into_response()
+}
+
 async fn get_picture(
     State(backend): State<Backend>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
-    match backend.get_image(id).await {
-        Err(err) =>{
+    let metadata = match backend.get_image_metadata(id).await {
+        Err(err) => {
+            warn!(?err, image_id = ?id, "Error while trying to get image metadata");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Error occured trying to download image")
+                .into_response();
+        }
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, "Couldn't find this image")
+                .into_response();
+        }
+        Ok(Some(metadata)) => metadata,
+    };
+
+    let etag = metadata.etag.as_deref();
+
+    let if_none_match = headers.get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok());
+    if let Some(if_none_match) = if_none_match {
+        if etag_matches(if_none_match, etag) {
+            let mut response = StatusCode::NOT_MODIFIED.into_response();
+            if let Some(etag) = etag {
+                response.headers_mut().insert(header::ETAG, etag.parse().unwrap());
+            }
+            return response;
+        }
+    }
+
+    // If-Range means: only send the range if the image didn't change
+    let if_range_matches = headers.get(header::IF_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .is_none_or(|if_range| etag.is_some_and(|etag| etag == if_range));
+
+    let range_header = headers.get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|_| if_range_matches);
+
+    let range = match range_header {
+        None => None,
+        Some(range_header) => {
+            let range = range_header.strip_prefix("bytes=")
+                .and_then(|spec| ByteRange::resolve(spec, metadata.size));
+            match range {
+                Some(range) => Some(range),
+                None => {
+                    return (
+                        StatusCode::RANGE_NOT_SATISFIABLE,
+                        [(header::CONTENT_RANGE, format!("bytes */{}", metadata.size))],
+                    ).into_response();
+                }
+            }
+        }
+    };
+
+    let image = match backend.stream_image(id, range).await {
+        Err(err) => {
             warn!(?err, image_id = ?id, "Error while trying to download image");
-            (StatusCode::INTERNAL_SERVER_ERROR, "Error occured trying to download image from S3")
-            .into_response()
-        },
-        Ok(Some(bytes)) =>
-            ([
-                (header::CACHE_CONTROL, "public, max-age=604800, immutable".to_string()),
-                (header::CONTENT_TYPE, bytes.0)
-                ], bytes.1)
-                .into_response(),
-        Ok(None) =>
-            (StatusCode::NOT_FOUND, "Couldn't find this image")
-                .into_response()
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Error occured trying to download image")
+                .into_response();
+        }
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, "Couldn't find this image")
+                .into_response();
+        }
+        Ok(Some(image)) => image,
+    };
+
+    // the store may have cut the range short if the image changed in between
+    let status = if image.range.is_some() { StatusCode::PARTIAL_CONTENT } else { StatusCode::OK };
+
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CACHE_CONTROL, "public, max-age=604800, immutable")
+        .header(header::CONTENT_TYPE, &image.metadata.content_type)
+        .header(header::CONTENT_LENGTH, image.content_length())
+        .header(header::ACCEPT_RANGES, "bytes");
+
+    if let Some(etag) = &image.metadata.etag {
+        response = response.header(header::ETAG, etag);
     }
+
+    if let Some(range) = image.range {
+        response = response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", range.start, range.end, image.metadata.size)
+        );
+    }
+
+    response.body(Body::from_stream(image.body))
+        .unwrap()
+        .into_response()
+}
+
+/// Checks an If-None-Match header against the ETag of an image.
+fn etag_matches(if_none_match: &str, etag: Option<&str>) -> bool {
+    if if_none_match.trim() == "*" {
+        return true;
+    }
+
+    let Some(etag) = etag else {
+        return false;
+    };
+
+    if_none_match.split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == etag)
 }
 
 async fn remove_picture() {
@@ -1,24 +1,190 @@
-use axum::{extract::State, routing::post, Json, http::StatusCode, Router, response::IntoResponse};
-use futures::future::try_join_all;
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{extract::{Path, Query, State}, routing::{get, post, put}, Json, http::{header, HeaderMap, StatusCode}, Router, response::{sse::{Event, KeepAlive, Sse}, IntoResponse, Response}};
+use futures::{Stream, StreamExt};
 use postgis_diesel::types::Point;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
 use tracing::{error, warn};
 use uuid::Uuid;
 
-use crate::{auth::AuthSession, backend::{recognition::{PlantRecogniser, PlantRecognitionInfo}, Backend}, AppState};
+use crate::{
+    auth::{is_admin, is_trusted, AuthSession},
+    backend::{recognition::RecognitionError, recognition_jobs::RecognitionJobState, Backend, BackendError, PlantQuery, RecognitionRequest},
+    config::AppConfig,
+    models::{Organ, PlantCare},
+    AppState,
+};
+
+use super::user_language;
 
 pub fn router() -> Router<AppState> {
     Router::new()
+        .route("/", get(search_plants))
+        .route("/:id", get(get_plant))
+        .route("/:id/care", put(edit_plant_care))
+        .route("/:id/names", get(get_plant_names))
+        .route("/:id/similar", get(get_similar_plants))
+        .route("/:id/restrictions", get(get_plant_restrictions))
         .route("/recognise", post(recognise_plant))
+        .route("/recognise/:job_id", get(get_recognition_job))
+        .route("/recognise/:job_id/events", get(recognition_job_events))
+}
+
+async fn search_plants(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    headers: HeaderMap,
+    Query(query): Query<PlantQuery>,
+) -> impl IntoResponse {
+    match backend.search_plants(&query).await {
+        Ok(mut plants) => {
+            let language = user_language(&backend, &auth_session, &headers).await;
+            if let Err(err) = backend.localize_plants(&mut plants, &language).await {
+                error!(?err, language, "Error while localizing plant names");
+            }
+            Json(plants).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?query, "Error while searching plants");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while searching plants").into_response()
+        }
+    }
+}
+
+async fn get_plant(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match backend.get_plant(id).await {
+        Ok(Some(mut plant)) => {
+            let language = user_language(&backend, &auth_session, &headers).await;
+            if let Err(err) = backend.localize_plants([&mut plant], &language).await {
+                error!(?err, language, "Error while localizing plant name");
+            }
+            Json(plant).into_response()
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting plant");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while getting plant").into_response()
+        }
+    }
+}
+
+/// Common names of the plant in all languages we know.
+async fn get_plant_names(
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match backend.get_plant_names(id).await {
+        Ok(names) => Json(names).into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting plant names");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while getting plant names").into_response()
+        }
+    }
+}
+
+async fn get_similar_plants(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match backend.get_similar_plants(id).await {
+        Ok(mut similar) => {
+            let language = user_language(&backend, &auth_session, &headers).await;
+            if let Err(err) = backend.localize_plants(similar.iter_mut().map(|similar| &mut similar.plant), &language).await {
+                error!(?err, language, "Error while localizing plant names");
+            }
+            Json(similar).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?id, "Error while getting similar plants");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while getting similar plants").into_response()
+        }
+    }
+}
+
+/// Trade restrictions of the plant where the user lives, or the ones
+/// that apply everywhere for anonymous users.
+async fn get_plant_restrictions(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().map(|user| user.claims.user_id);
+
+    match backend.get_plant_restrictions(id, user).await {
+        Ok(restrictions) => Json(restrictions).into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting trade restrictions of plant");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while getting trade restrictions").into_response()
+        }
+    }
+}
+
+/// Only trusted users can edit, their changes wait for a moderator.
+/// Changes of admins are applied right away.
+async fn edit_plant_care(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    State(config): State<Arc<AppConfig>>,
+    Path(id): Path<Uuid>,
+    Json(changes): Json<PlantCare>,
+) -> impl IntoResponse {
+    if !is_trusted(&auth_session, &config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let author = auth_session.user.as_ref().unwrap().claims.user_id;
+    let approved = is_admin(&auth_session, &config);
+
+    match backend.propose_plant_edit(id, author, &changes, approved).await {
+        Ok(Some(edit)) if approved => (StatusCode::OK, Json(edit)).into_response(),
+        Ok(Some(edit)) => (StatusCode::ACCEPTED, Json(edit)).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(BackendError::InvalidPlantEdit(message)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?id, "Error while editing plant care information");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while editing plant").into_response()
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 struct RecognisePlantInput {
-    pub images: Vec<Uuid>,
+    pub images: Vec<RecogniseImage>,
     #[serde(default)]
     pub location: Option<Location>,
 }
 
+/// Either just the image id, or the id with the organ on the picture.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum RecogniseImage {
+    Id(Uuid),
+    WithOrgan {
+        id: Uuid,
+        #[serde(default)]
+        organ: Organ,
+    },
+}
+
+impl RecogniseImage {
+    fn into_parts(self) -> (Uuid, Organ) {
+        match self {
+            RecogniseImage::Id(id) => (id, Organ::Auto),
+            RecogniseImage::WithOrgan { id, organ } => (id, organ),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 struct Location {
     x: f64,
@@ -37,61 +203,121 @@ impl Location {
 
     pub fn to_point(&self) -> Point {
         let rounded = self.round();
-        Point::new(rounded.x, rounded.y, None)
+        Point::new(rounded.x, rounded.y, Some(4326))
     }
 }
 
+pub(super) fn recognition_error_response(err: RecognitionError) -> Response {
+    match err {
+        RecognitionError::NoMatch => (StatusCode::NOT_FOUND, "No plant was recognised on the pictures")
+            .into_response(),
+        RecognitionError::InvalidPictures(message) => {
+            warn!(message, "Plant recogniser rejected the pictures");
+            (StatusCode::UNPROCESSABLE_ENTITY, format!("The pictures couldn't be analyzed: {message}"))
+                .into_response()
+        }
+        RecognitionError::QuotaExceeded => {
+            warn!("Plant recognition quota exceeded");
+            (StatusCode::SERVICE_UNAVAILABLE, "Plant recognition is unavailable for today, try again tomorrow")
+                .into_response()
+        }
+        RecognitionError::Unavailable(message) => {
+            error!(message, "Plant recogniser unavailable");
+            (StatusCode::BAD_GATEWAY, "Plant recognition is currently unavailable")
+                .into_response()
+        }
+        RecognitionError::Db(err) => {
+            error!(?err, "Plant analysis failed");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Plant analysis failed")
+                .into_response()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RecognitionJobCreated {
+    pub job_id: Uuid,
+}
+
+/// Queues the recognition, the result can be polled or subscribed to
+/// with the returned job id.
 async fn recognise_plant(
-    _auth_session: AuthSession,
+    auth_session: AuthSession,
     State(backend): State<Backend>,
+    headers: HeaderMap,
     Json(input): Json<RecognisePlantInput>,
 ) -> impl IntoResponse {
-    let image_uuids = input.images;
+    let user = auth_session.user.as_ref().map(|user| user.claims.user_id);
 
-    let fetch_image_results: Result<Vec<_>, _> = try_join_all(image_uuids.iter()
-        .map(|uuid| async {
-            backend.get_image(*uuid).await
-        }))
-        .await;
+    let request = RecognitionRequest {
+        pictures: input.images.into_iter().map(RecogniseImage::into_parts).collect(),
+        location: input.location.map(|l| l.to_point()),
+        language: user_language(&backend, &auth_session, &headers).await,
+    };
 
-    let maybe_missing_images = match fetch_image_results {
-        Ok(images) => images,
+    match backend.recognition_jobs.submit(user, request) {
+        Ok(job_id) => (
+            StatusCode::ACCEPTED,
+            [(header::LOCATION, format!("/api/v1/plant/recognise/{job_id}"))],
+            Json(RecognitionJobCreated { job_id }),
+        ).into_response(),
+        Err(BackendError::RecognitionQueueFull) => {
+            warn!("Recognition queue is full");
+            (StatusCode::SERVICE_UNAVAILABLE, "Too many plant recognitions right now, try again later")
+                .into_response()
+        }
         Err(err) => {
-            error!(?err, "Error while trying to download image");
-            return (StatusCode::INTERNAL_SERVER_ERROR, "Error while trying to download image")
-                .into_response();
+            error!(?err, "Couldn't queue plant recognition");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Plant analysis failed")
+                .into_response()
         }
-    };
+    }
+}
+
+async fn get_recognition_job(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(job_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().map(|user| user.claims.user_id);
 
-    if let Some(index) = maybe_missing_images.iter().position(|image| image.is_none()) {
-        let uuid = image_uuids[index];
-        warn!(?uuid, "Couldn't find image");
-        return (StatusCode::BAD_REQUEST, format!("Couldn't find image with uuid={uuid}"))
-            .into_response();
+    match backend.recognition_jobs.state(job_id, user) {
+        Some(state) => Json(state).into_response(),
+        None => (StatusCode::NOT_FOUND, "Recognition job not found").into_response(),
     }
+}
 
-    let images = maybe_missing_images.into_iter()
-        .map(|img| img.unwrap().1)
-        .zip(image_uuids.iter().map(Uuid::to_string))
-        .collect();
+/// Sends the job's state as `state` events, the stream ends once it's finished.
+async fn recognition_job_events(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Path(job_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().map(|user| user.claims.user_id);
 
-    let location = input.location.map(|l| l.to_point());
+    match backend.recognition_jobs.subscribe(job_id, user) {
+        Some(receiver) => Sse::new(job_state_events(receiver))
+            .keep_alive(KeepAlive::default())
+            .into_response(),
+        None => (StatusCode::NOT_FOUND, "Recognition job not found").into_response(),
+    }
+}
 
-    let info = PlantRecognitionInfo {
-        images,
-        location,
-    };
+fn job_state_events(receiver: watch::Receiver<RecognitionJobState>) -> impl Stream<Item = Result<Event, Infallible>> {
+    // (receiver, is it the first event, was the last event the final one)
+    futures::stream::unfold((receiver, true, false), |(mut receiver, first, finished)| async move {
+        if finished || (!first && receiver.changed().await.is_err()) {
+            return None;
+        }
 
-    let mut db = backend.db.lock().await;
+        let state = receiver.borrow_and_update().clone();
+        let finished = state.is_finished();
 
-    let plant_analysis = backend.plant_recognition.analyze_plant(&mut db, &info).await;
+        let event = Event::default()
+            .event("state")
+            .json_data(&state)
+            .expect("job states are always valid json");
 
-    match plant_analysis {
-        Ok(plants) => (StatusCode::OK, Json(plants)).into_response(),
-        Err(err) => {
-            error!(?err, "Plant analysis failed");
-            (StatusCode::INTERNAL_SERVER_ERROR, "Plant analysis failed")
-                .into_response()
-        }
-    }
+        Some((event, (receiver, false, finished)))
+    }).map(Ok)
 }
@@ -0,0 +1,57 @@
+use axum::{extract::{Path, Query, State}, http::{HeaderMap, StatusCode}, response::IntoResponse, routing::get, Json, Router};
+use serde::Deserialize;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{auth::AuthSession, backend::Backend, AppState};
+
+use super::user_language;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(search_taxa))
+        .route("/:id", get(get_taxon))
+}
+
+#[derive(Deserialize, Debug)]
+struct TaxonQuery {
+    /// Start of the family or genus name
+    #[serde(default)]
+    pub search: String,
+}
+
+async fn search_taxa(
+    State(backend): State<Backend>,
+    Query(query): Query<TaxonQuery>,
+) -> impl IntoResponse {
+    match backend.search_taxa(&query.search).await {
+        Ok(taxa) => Json(taxa).into_response(),
+        Err(err) => {
+            error!(?err, ?query, "Error while searching taxa");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while searching taxa").into_response()
+        }
+    }
+}
+
+/// The family or genus with its genera and plants.
+async fn get_taxon(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match backend.get_taxon(id).await {
+        Ok(Some(mut taxon)) => {
+            let language = user_language(&backend, &auth_session, &headers).await;
+            if let Err(err) = backend.localize_plants(&mut taxon.plants, &language).await {
+                error!(?err, language, "Error while localizing plant names");
+            }
+            Json(taxon).into_response()
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!(?err, ?id, "Error while getting taxon");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while getting taxon").into_response()
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use axum::{extract::State, http::{HeaderMap, StatusCode}, response::IntoResponse, routing::get, Json, Router};
+use axum_login::login_required;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{auth::{AuthSession, AuthState}, backend::{Backend, BackendError}, AppState};
+
+use super::request_language;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/language", get(get_language).put(set_language))
+        .route_layer(login_required!(AuthState, login_url = crate::LOGIN_URL))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct LanguagePreference {
+    /// ISO 639-1 code, None to use the browser's language
+    pub language: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LanguageResponse {
+    /// From the profile
+    pub preferred: Option<String>,
+    /// The one plant names are shown in right now
+    pub effective: String,
+}
+
+async fn get_language(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    match backend.get_preferred_language(user).await {
+        Ok(preferred) => {
+            let effective = preferred.clone().unwrap_or_else(|| request_language(&headers));
+            Json(LanguageResponse { preferred, effective }).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?user, "Error while getting preferred language");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while getting preferred language").into_response()
+        }
+    }
+}
+
+async fn set_language(
+    auth_session: AuthSession,
+    State(backend): State<Backend>,
+    Json(preference): Json<LanguagePreference>,
+) -> impl IntoResponse {
+    let user = auth_session.user.as_ref().unwrap().claims.user_id;
+
+    match backend.set_preferred_language(user, preference.language.as_deref()).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(BackendError::InvalidLanguage(language)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, format!("\"{language}\" is not an ISO 639-1 language code")).into_response()
+        }
+        Err(err) => {
+            error!(?err, ?user, ?preference, "Error while setting preferred language");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error while setting preferred language").into_response()
+        }
+    }
+}
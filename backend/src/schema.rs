@@ -1,13 +1,73 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "identification_state"))]
+    pub struct IdentificationState;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "light_requirement"))]
+    pub struct LightRequirement;
+
     #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "listing_type"))]
     pub struct ListingType;
 
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "plant_edit_status"))]
+    pub struct PlantEditStatus;
+
     #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "plant_location"))]
     pub struct PlantLocation;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "plant_organ"))]
+    pub struct PlantOrgan;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "plant_relation"))]
+    pub struct PlantRelation;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "plant_season"))]
+    pub struct PlantSeason;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "price_mode"))]
+    pub struct PriceMode;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "propagation_form"))]
+    pub struct PropagationForm;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "propagation_method"))]
+    pub struct PropagationMethod;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "restriction_level"))]
+    pub struct RestrictionLevel;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "taxon_rank"))]
+    pub struct TaxonRank;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "watering_frequency"))]
+    pub struct WateringFrequency;
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    identification_votes (listing_id, voter) {
+        listing_id -> Uuid,
+        voter -> Uuid,
+        plant -> Uuid,
+        voted_at -> Timestamp,
+    }
 }
 
 diesel::table! {
@@ -18,6 +78,94 @@ diesel::table! {
         file_key -> Uuid,
         uploaded_by_user -> Nullable<Uuid>,
         upload_date -> Timestamp,
+        pending_until -> Nullable<Timestamp>,
+        phash -> Nullable<Int8>,
+        size_bytes -> Int8,
+        #[max_length = 64]
+        blurhash -> Nullable<Varchar>,
+        #[max_length = 7]
+        dominant_color -> Nullable<Varchar>,
+        #[max_length = 64]
+        content_hash -> Nullable<Varchar>,
+        staging_key -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    listing_draft_pictures (draft_id, image) {
+        draft_id -> Uuid,
+        image -> Uuid,
+        position -> Int2,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+    use super::sql_types::ListingType;
+    use super::sql_types::PropagationForm;
+    use super::sql_types::PriceMode;
+
+    listing_drafts (id) {
+        id -> Uuid,
+        author -> Uuid,
+        #[max_length = 120]
+        title -> Nullable<Varchar>,
+        #[max_length = 1023]
+        description -> Nullable<Varchar>,
+        listing_type -> Nullable<ListingType>,
+        tradeable -> Nullable<Bool>,
+        identified_plant -> Nullable<Uuid>,
+        propagation_form -> Nullable<PropagationForm>,
+        quantity -> Nullable<Int4>,
+        pot_size_cm -> Nullable<Int2>,
+        price_mode -> Nullable<PriceMode>,
+        price_minor -> Nullable<Int8>,
+        #[max_length = 3]
+        currency -> Nullable<Bpchar>,
+        price_negotiable -> Nullable<Bool>,
+        local_pickup -> Nullable<Bool>,
+        shipping -> Nullable<Bool>,
+        shipping_cost_minor -> Nullable<Int8>,
+        #[max_length = 3]
+        shipping_currency -> Nullable<Bpchar>,
+        meet_halfway -> Nullable<Bool>,
+        max_pickup_distance_km -> Nullable<Float8>,
+        publish_at -> Nullable<Timestamp>,
+        publish_error -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    listing_pictures (listing_id, image) {
+        listing_id -> Uuid,
+        image -> Uuid,
+        position -> Int2,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+    use super::sql_types::PriceMode;
+
+    listing_price_history (id) {
+        id -> Uuid,
+        listing_id -> Uuid,
+        price_mode -> Nullable<PriceMode>,
+        price_minor -> Nullable<Int8>,
+        #[max_length = 3]
+        currency -> Nullable<Bpchar>,
+        price_negotiable -> Bool,
+        changed_at -> Timestamp,
     }
 }
 
@@ -25,6 +173,9 @@ diesel::table! {
     use diesel::sql_types::*;
     use postgis_diesel::sql_types::*;
     use super::sql_types::ListingType;
+    use super::sql_types::IdentificationState;
+    use super::sql_types::PropagationForm;
+    use super::sql_types::PriceMode;
 
     listings (id) {
         id -> Uuid,
@@ -38,6 +189,67 @@ diesel::table! {
         thumbnail -> Uuid,
         tradeable -> Bool,
         identified_plant -> Nullable<Uuid>,
+        identification_state -> Nullable<IdentificationState>,
+        propagation_form -> PropagationForm,
+        quantity -> Int4,
+        pot_size_cm -> Nullable<Int2>,
+        price_mode -> Nullable<PriceMode>,
+        price_minor -> Nullable<Int8>,
+        #[max_length = 3]
+        currency -> Nullable<Bpchar>,
+        price_negotiable -> Bool,
+        local_pickup -> Bool,
+        shipping -> Bool,
+        shipping_cost_minor -> Nullable<Int8>,
+        #[max_length = 3]
+        shipping_currency -> Nullable<Bpchar>,
+        meet_halfway -> Bool,
+        max_pickup_distance_km -> Nullable<Float8>,
+        suggested_plant -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+    use super::sql_types::PlantEditStatus;
+
+    plant_edits (id) {
+        id -> Uuid,
+        plant_id -> Uuid,
+        author -> Uuid,
+        changes -> Jsonb,
+        status -> PlantEditStatus,
+        created_at -> Timestamp,
+        reviewed_by -> Nullable<Uuid>,
+        reviewed_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    plant_names (plant_id, lang, name) {
+        plant_id -> Uuid,
+        #[max_length = 2]
+        lang -> Varchar,
+        #[max_length = 127]
+        name -> Varchar,
+        is_primary -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+    use super::sql_types::PlantRelation;
+
+    plant_similarity (plant_a, plant_b, relation) {
+        plant_a -> Uuid,
+        plant_b -> Uuid,
+        relation -> PlantRelation,
+        weight -> Float4,
     }
 }
 
@@ -45,6 +257,10 @@ diesel::table! {
     use diesel::sql_types::*;
     use postgis_diesel::sql_types::*;
     use super::sql_types::PlantLocation;
+    use super::sql_types::WateringFrequency;
+    use super::sql_types::LightRequirement;
+    use super::sql_types::PropagationMethod;
+    use super::sql_types::PlantSeason;
 
     plants (id) {
         id -> Uuid,
@@ -59,6 +275,33 @@ diesel::table! {
         produces_fruit -> Nullable<Bool>,
         #[max_length = 1023]
         description -> Varchar,
+        watering -> Nullable<WateringFrequency>,
+        light -> Nullable<LightRequirement>,
+        hardiness_zone -> Nullable<Int2>,
+        toxic_to_pets -> Nullable<Bool>,
+        propagation_methods -> Array<PropagationMethod>,
+        season -> Nullable<PlantSeason>,
+        genus -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+    use super::sql_types::PlantOrgan;
+
+    recognitions (id) {
+        id -> Uuid,
+        #[max_length = 64]
+        cache_key -> Varchar,
+        images -> Array<Uuid>,
+        location -> Nullable<Geography>,
+        results -> Jsonb,
+        created_at -> Timestamp,
+        organs -> Array<PlantOrgan>,
+        #[max_length = 8]
+        language -> Varchar,
+        region -> Nullable<Geography>,
     }
 }
 
@@ -78,6 +321,64 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    suspected_duplicates (id) {
+        id -> Uuid,
+        listing_id -> Uuid,
+        image -> Uuid,
+        matched_image -> Uuid,
+        distance -> Int2,
+        flagged_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+    use super::sql_types::TaxonRank;
+
+    taxa (id) {
+        id -> Uuid,
+        rank -> TaxonRank,
+        #[max_length = 127]
+        name -> Varchar,
+        parent -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+    use super::sql_types::RestrictionLevel;
+
+    trade_restrictions (id) {
+        id -> Uuid,
+        powo_id -> Nullable<Varchar>,
+        taxon_id -> Nullable<Uuid>,
+        level -> RestrictionLevel,
+        reason -> Text,
+        #[max_length = 127]
+        region_name -> Nullable<Varchar>,
+        region -> Nullable<Geography>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    upload_quotas (user_id) {
+        user_id -> Uuid,
+        max_bytes -> Nullable<Int8>,
+        max_images -> Nullable<Int8>,
+        max_per_hour -> Nullable<Int8>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use postgis_diesel::sql_types::*;
@@ -96,18 +397,46 @@ diesel::table! {
     users (id) {
         id -> Uuid,
         location -> Nullable<Geography>,
+        #[max_length = 2]
+        preferred_language -> Nullable<Varchar>,
     }
 }
 
+diesel::joinable!(identification_votes -> listings (listing_id));
+diesel::joinable!(identification_votes -> plants (plant));
+diesel::joinable!(listing_draft_pictures -> images (image));
+diesel::joinable!(listing_draft_pictures -> listing_drafts (draft_id));
+diesel::joinable!(listing_drafts -> plants (identified_plant));
+diesel::joinable!(listing_pictures -> images (image));
+diesel::joinable!(listing_pictures -> listings (listing_id));
+diesel::joinable!(listing_price_history -> listings (listing_id));
 diesel::joinable!(listings -> images (thumbnail));
 diesel::joinable!(listings -> plants (identified_plant));
 diesel::joinable!(listings -> users (author));
+diesel::joinable!(plant_edits -> plants (plant_id));
+diesel::joinable!(plant_names -> plants (plant_id));
+diesel::joinable!(plants -> taxa (genus));
+diesel::joinable!(suspected_duplicates -> listings (listing_id));
+diesel::joinable!(trade_restrictions -> taxa (taxon_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    identification_votes,
     images,
+    listing_draft_pictures,
+    listing_drafts,
+    listing_pictures,
+    listing_price_history,
     listings,
+    plant_edits,
+    plant_names,
+    plant_similarity,
     plants,
+    recognitions,
     spatial_ref_sys,
+    suspected_duplicates,
+    taxa,
+    trade_restrictions,
+    upload_quotas,
     user_sessions,
     users,
 );